@@ -3,13 +3,38 @@ import test from 'ava';
 import * as jsSrpClient from '@ruc-cheese/secure-remote-password/client.js';
 import * as jsSrpServer from '@ruc-cheese/secure-remote-password/server.js';
 // Rust implementation
-import { Client as RustSrpClient, Server as RustSrpServer, SrpGroup, srpGroupFromValue } from '../index.js';
+import {
+  Client as RustSrpClient,
+  Server as RustSrpServer,
+  ChallengeStore,
+  SrpGroup,
+  SrpHash,
+  SrpEncoding,
+  SrpError,
+  CustomGroup,
+  srpGroupFromValue,
+  groupParameters,
+  validateGroup,
+  KdfAlgorithm,
+  encodeKdfParams,
+  decodeKdfParams,
+} from '../index.js';
 import crypto from 'crypto';
+import { execFileSync } from 'child_process';
+import { fileURLToPath } from 'url';
 
 // Initialize Rust implementation
 const rustClient = new RustSrpClient();
 const rustServer = new RustSrpServer();
 
+// withTestRng only exists in builds with the test-rng cargo feature (yarn build:test).
+// Its tests are skipped without it, unless SRP_TEST_RNG asks for them as CI does.
+const hasTestRng = typeof RustSrpClient.withTestRng === 'function';
+if (!hasTestRng && process.env.SRP_TEST_RNG) {
+  throw new Error('SRP_TEST_RNG is set, but the addon was built without the test-rng feature');
+}
+const testWithRng = hasTestRng ? test : test.skip;
+
 // Test user data
 const TEST_USERNAME = 'test_user@example.com';
 const TEST_PASSWORD = 'secure_password123!';
@@ -288,6 +313,61 @@ test('Invalid server ephemeral should be rejected', t => {
   t.is(error.message, 'Server\'s public ephemeral value is invalid');
 });
 
+// RFC 5054 2048-bit prime (the default group)
+const N_2048 = BigInt('0xAC6BDB41324A9A9BF166DE5E1389582FAF72B6651987EE07FC3192943DB56050A37329CBB4A099ED8193E0757767A13DD52312AB4B03310DCD7F48A9DA04FD50E8083969EDB767B0CF6095179A163AB3661A05FBD5FAAAE82918A9962F0B93B855F97993EC975EEAA80D740ADBF4FF747359D041D5C33EA71D281E446B14773BCA97B43A23FB801676BD207A436C6481F1D2B9078717461A5B9D32E688F87748544523B524B0D57D5EA77A2775D2ECFA032CFBDBF52FB3786160279004E57AE6AF874E7303CE53299CCC041C7BC308D82A5698F3A8D0C38271AE35F8E9DBFBB694B5C803D89F7AE435DE236D525F54759B65E372FCD68EF20FA7111F9E4AFF73');
+
+// Malicious public ephemerals and the reason each one is rejected
+const MALICIOUS_EPHEMERALS = [
+  ['0', 'is invalid'],
+  [N_2048.toString(16), 'is out of range'],
+  [(N_2048 + 1n).toString(16), 'is out of range'],
+  [(N_2048 * 2n).toString(16), 'is out of range'],
+  ['1', 'is in a small subgroup'],
+  [(N_2048 - 1n).toString(16), 'is in a small subgroup'],
+];
+
+test('Malicious server ephemerals should be rejected', t => {
+  const salt = jsSrpClient.generateSalt();
+  const privateKey = jsSrpClient.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const clientEphemeral = rustClient.generateEphemeral();
+
+  for (const [B, reason] of MALICIOUS_EPHEMERALS) {
+    const error = t.throws(() => {
+      rustClient.deriveSession(
+        clientEphemeral.secret,
+        B,
+        salt,
+        TEST_USERNAME,
+        privateKey
+      );
+    });
+
+    t.is(error.message, `Server's public ephemeral value ${reason}`);
+  }
+});
+
+test('Malicious client ephemerals should be rejected', t => {
+  const salt = jsSrpClient.generateSalt();
+  const privateKey = jsSrpClient.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = jsSrpClient.deriveVerifier(privateKey);
+  const serverEphemeral = rustServer.generateEphemeral(verifier);
+
+  for (const [A, reason] of MALICIOUS_EPHEMERALS) {
+    const error = t.throws(() => {
+      rustServer.deriveSession(
+        serverEphemeral.secret,
+        A,
+        salt,
+        TEST_USERNAME,
+        verifier,
+        'abcdef1234567890'
+      );
+    });
+
+    t.is(error.message, `Client's public ephemeral value ${reason}`);
+  }
+});
+
 test('Invalid client proof should be rejected', t => {
   const salt = jsSrpClient.generateSalt();
   const privateKey = jsSrpClient.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
@@ -312,6 +392,165 @@ test('Invalid client proof should be rejected', t => {
   t.is(error.message, 'Client\'s proof is invalid');
 });
 
+test('Proofs should be compared as fixed-length byte strings', t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, 'alice', 'password123');
+  const verifier = client.deriveVerifier(privateKey);
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+  const clientSession = client.deriveSession(
+    clientEphemeral.secret,
+    serverEphemeral.public,
+    salt,
+    'alice',
+    privateKey
+  );
+
+  const flipLastByte = proof =>
+    proof.slice(0, -2) + ((parseInt(proof.slice(-2), 16) ^ 1).toString(16).padStart(2, '0'));
+
+  // Same integer value, different encodings: a big-integer comparison would accept these
+  const tamperedClientProofs = [
+    '00' + clientSession.proof,
+    clientSession.proof.slice(2),
+    clientSession.proof + '00',
+    flipLastByte(clientSession.proof)
+  ];
+
+  for (const proof of tamperedClientProofs) {
+    const error = t.throws(() => {
+      server.deriveSession(serverEphemeral.secret, clientEphemeral.public, salt, 'alice', verifier, proof);
+    });
+    t.is(error.message, 'Client\'s proof is invalid');
+  }
+
+  const serverSession = server.deriveSession(
+    serverEphemeral.secret,
+    clientEphemeral.public,
+    salt,
+    'alice',
+    verifier,
+    clientSession.proof
+  );
+
+  const tamperedServerProofs = [
+    '00' + serverSession.proof,
+    serverSession.proof.slice(2),
+    flipLastByte(serverSession.proof)
+  ];
+
+  for (const proof of tamperedServerProofs) {
+    const error = t.throws(() => {
+      client.verifySession(clientEphemeral.public, clientSession, proof);
+    });
+    t.is(error.message, 'Server\'s proof is invalid');
+  }
+
+  t.notThrows(() => {
+    client.verifySession(clientEphemeral.public, clientSession, serverSession.proof);
+  });
+});
+
+// ===== Randomness tests =====
+
+test('Fresh processes should never generate the same secret ephemeral', t => {
+  const PROCESSES = 4;
+  const bindingPath = fileURLToPath(new URL('../index.cjs', import.meta.url));
+  const script = `
+    const { Client, Server } = require(${JSON.stringify(bindingPath)});
+    const client = new Client();
+    const server = new Server();
+    const verifier = client.deriveVerifier(client.derivePrivateKey(client.generateSalt(), 'u', 'p'));
+    process.stdout.write(JSON.stringify({
+      salt: client.generateSalt(),
+      client: client.generateEphemeral().secret,
+      server: server.generateEphemeral(verifier).secret
+    }));
+  `;
+
+  // Each run starts from a freshly loaded native module, so a fixed-seed RNG would repeat here
+  const runs = Array.from({ length: PROCESSES }, () =>
+    JSON.parse(execFileSync(process.execPath, ['-e', script], { encoding: 'utf8' }))
+  );
+
+  for (const key of ['salt', 'client', 'server']) {
+    const values = runs.map(run => run[key]);
+    t.is(new Set(values).size, PROCESSES, `Each process should produce a distinct ${key} value`);
+    for (const value of values) {
+      t.is(value.length, 64, `${key} value should be a 32-byte hex string`);
+    }
+  }
+});
+
+// Known-answer values for withTestRng(1) on the client and withTestRng(2) on the server
+const KAT = {
+  salt: '9a3744504560639ec670b7a17d492b273e077b0a96bef58ba7760779e544546e',
+  clientSecret: '000efec87c5749ec1157912e0e171f60de9e5341348819a2de99f140c59a424c',
+  clientPublic: '055d0c13d154fe1754b49e850ed63f10fd16503538b04823caf8ba14a1b55cefda30f9ab1c7ab47815ceda444aae33d97584e76a355b24331bb7929bdd45167d21c4166c4d198598bff2ad24b15b317fa6219dd81ae81468618df55a9d8f8963c0db73b2362e83f303a7b917142d24dd7459756a3132247f0f1d97ef39ae56be6eddc5eb310a6815cc8493c9de8fa85f575812aba130b6250350c96c3db13f406826c37139c877765c829ec41142a4a9a12de2c7de271d27b6753ac3fbc562e43c38ffecd6ce69b8accdf5b0a16d84d97635b0809c64f08b3ef7fc91d45a564447973e5ff39383d84f43b35fac00b3a09bf44dcc9b8bad38df42d2aa1a57ded9',
+  serverSecret: '8e0bb7534fcf4f12ac7458fb8092fe529ce56ea0bb68edb63b5f6ccb168aad93',
+  serverPublic: '9b3ebcee58727cae4bbfaa3e893031d7764b26624c08b1af2e97ea3dd917c777aaf7cc4bb1d2d2487715ae298497f28bf5ba8b24cbe3350c02cbec07bfbf5ee56341772f6bd7637e3b3fb2f9cedec897d34cd99e3e5bc6f2cca8ae929cb085141a257b873a047c001c38b8f7023f3eba548246ebb89fb6cb917058fc97865bce2d7867026653e71b94098ff16000d4f6bed7d63fe123316cd368f0d0131ef041bd56ed783f9584374bb961630ddf152e3875b5e3666f3efce69f26542f5e79a7573ab6f587394efce1e299d517f733f922dc8d7e33434ada0222cdde13ba3d284f53a6d5ffa5634117db6f1aed4625c47d286282537c6f7ab3c7533d3bda7ad2',
+  key: '5e4dd047166af23564017510157e69487c3a96af28f2cfc8c93e8f7a7a7a3d75',
+  clientProof: '450786d10ef72f882e8b8a1aa6a82d845e2c1d68ccd665de2c338b2ac399fc11',
+  serverProof: '808d83e64d1af6f0cdfa66c9f2dbd5911c1f99ecd82453a6a524044e516a1c51'
+};
+
+testWithRng('Seeded test RNG should reproduce a known handshake', t => {
+  const client = RustSrpClient.withTestRng(1);
+  const server = RustSrpServer.withTestRng(2);
+
+  const salt = client.generateSalt();
+  t.is(salt, KAT.salt, 'Salt should match the known answer');
+
+  const privateKey = client.derivePrivateKey(salt, 'alice', 'password123');
+  const verifier = client.deriveVerifier(privateKey);
+
+  const clientEphemeral = client.generateEphemeral();
+  t.is(clientEphemeral.secret, KAT.clientSecret, 'Client secret ephemeral should match the known answer');
+  t.is(clientEphemeral.public, KAT.clientPublic, 'Client public ephemeral should match the known answer');
+
+  const serverEphemeral = server.generateEphemeral(verifier);
+  t.is(serverEphemeral.secret, KAT.serverSecret, 'Server secret ephemeral should match the known answer');
+  t.is(serverEphemeral.public, KAT.serverPublic, 'Server public ephemeral should match the known answer');
+
+  const clientSession = client.deriveSession(
+    clientEphemeral.secret,
+    serverEphemeral.public,
+    salt,
+    'alice',
+    privateKey
+  );
+  t.is(clientSession.key, KAT.key, 'Session key should match the known answer');
+  t.is(clientSession.proof, KAT.clientProof, 'Client proof should match the known answer');
+
+  const serverSession = server.deriveSession(
+    serverEphemeral.secret,
+    clientEphemeral.public,
+    salt,
+    'alice',
+    verifier,
+    clientSession.proof
+  );
+  t.is(serverSession.proof, KAT.serverProof, 'Server proof should match the known answer');
+});
+
+testWithRng('Seeded test RNG should depend only on its seed', t => {
+  const first = RustSrpClient.withTestRng(7, SrpGroup.RFC5054_4096);
+  const second = RustSrpClient.withTestRng(7, SrpGroup.RFC5054_4096);
+  const other = RustSrpClient.withTestRng(8, SrpGroup.RFC5054_4096);
+
+  const firstEphemeral = first.generateEphemeral();
+  const secondEphemeral = second.generateEphemeral();
+  t.is(secondEphemeral.secret, firstEphemeral.secret, 'Same seed should give the same secret ephemeral');
+  t.is(secondEphemeral.public, firstEphemeral.public, 'Same seed should give the same public ephemeral');
+  t.not(other.generateEphemeral().secret, firstEphemeral.secret, 'Different seeds should give different ephemerals');
+
+  // The default constructor keeps using the OS CSPRNG
+  t.not(new RustSrpClient().generateSalt(), new RustSrpClient().generateSalt(), 'Default clients should not repeat salts');
+});
+
 // ===== Additional SRP parameter group tests =====
 
 test('SRP authentication flow with 1024-bit group', async t => {
@@ -402,12 +641,16 @@ test('srpGroupFromValue function should create correct group', t => {
   const group2048 = srpGroupFromValue(2048);
   const group3072 = srpGroupFromValue(3072);
   const group4096 = srpGroupFromValue(4096);
+  const group6144 = srpGroupFromValue(6144);
+  const group8192 = srpGroupFromValue(8192);
   
   t.deepEqual(group1024, SrpGroup.RFC5054_1024, 'Group 1024 created correctly');
   t.deepEqual(group1536, SrpGroup.RFC5054_1536, 'Group 1536 created correctly');
   t.deepEqual(group2048, SrpGroup.RFC5054_2048, 'Group 2048 created correctly');
   t.deepEqual(group3072, SrpGroup.RFC5054_3072, 'Group 3072 created correctly');
   t.deepEqual(group4096, SrpGroup.RFC5054_4096, 'Group 4096 created correctly');
+  t.deepEqual(group6144, SrpGroup.RFC5054_6144, 'Group 6144 created correctly');
+  t.deepEqual(group8192, SrpGroup.RFC5054_8192, 'Group 8192 created correctly');
   
   // Test invalid parameters
   t.throws(() => {
@@ -428,37 +671,30 @@ test('Mixing different parameter groups should fail', async t => {
   const clientEphemeral = client1024.generateEphemeral();
   const serverEphemeral = server4096.generateEphemeral(verifier);
   
-  // Client tries to calculate session (should fail because server uses different parameter group)
-  const clientSession = client1024.deriveSession(
-    clientEphemeral.secret,
-    serverEphemeral.public,
-    salt,
-    TEST_USERNAME,
-    privateKey
-  );
-  
-  // Server tries to verify client proof (should fail)
+  // Client tries to calculate session (should fail because the 4096-bit B
+  // is not a valid element of the 1024-bit group)
   const error = t.throws(() => {
-    server4096.deriveSession(
-      serverEphemeral.secret,
-      clientEphemeral.public,
+    client1024.deriveSession(
+      clientEphemeral.secret,
+      serverEphemeral.public,
       salt,
       TEST_USERNAME,
-      verifier,
-      clientSession.proof
+      privateKey
     );
   });
   
-  t.regex(error.message, /proof is invalid/, 'Mixing parameter groups should fail validation');
+  t.regex(error.message, /out of range/, 'Mixing parameter groups should fail validation');
 });
 
-test('All five parameter groups should work correctly', async t => {
+test('All seven parameter groups should work correctly', async t => {
   const groups = [
     SrpGroup.RFC5054_1024,
     SrpGroup.RFC5054_1536,
     SrpGroup.RFC5054_2048,
     SrpGroup.RFC5054_3072,
-    SrpGroup.RFC5054_4096
+    SrpGroup.RFC5054_4096,
+    SrpGroup.RFC5054_6144,
+    SrpGroup.RFC5054_8192
   ];
   
   for (const group of groups) {
@@ -495,6 +731,1089 @@ test('All five parameter groups should work correctly', async t => {
   }
 });
 
+// The reference JS implementation only ships the 2048-bit group, so the
+// large groups are checked against the same SRP-6a arithmetic in BigInt
+function referenceSrp({ prime, generator }) {
+  const N = BigInt('0x' + prime);
+  const g = BigInt('0x' + generator);
+  const pad = value => value.toString(16).padStart(prime.length, '0');
+  const toInt = hex => BigInt('0x' + hex);
+  const H = (...parts) => {
+    const hash = crypto.createHash('sha256');
+    for (const part of parts) hash.update(Buffer.from(part.length % 2 ? '0' + part : part, 'hex'));
+    return hash.digest('hex');
+  };
+  const modPow = (base, exp) => {
+    let result = 1n;
+    base %= N;
+    for (; exp > 0n; exp >>= 1n) {
+      if (exp & 1n) result = result * base % N;
+      base = base * base % N;
+    }
+    return result;
+  };
+  const mod = value => ((value % N) + N) % N;
+
+  const k = toInt(H(prime, generator));
+  const NgXor = (toInt(H(prime)) ^ toInt(H(generator))).toString(16).padStart(64, '0');
+  const proof = (username, salt, A, B, K) =>
+    H(NgXor, crypto.createHash('sha256').update(username).digest('hex'), salt, A, B, K);
+
+  return {
+    deriveVerifier: privateKey => pad(modPow(g, toInt(privateKey))),
+    generateEphemeral: verifier => {
+      const secret = crypto.randomBytes(32).toString('hex');
+      return { secret, public: pad(mod(k * toInt(verifier) + modPow(g, toInt(secret)))) };
+    },
+    clientSession: (secret, B, salt, username, privateKey) => {
+      const A = pad(modPow(g, toInt(secret)));
+      const u = toInt(H(A, B));
+      const x = toInt(privateKey);
+      const S = pad(modPow(mod(toInt(B) - k * modPow(g, x)), toInt(secret) + u * x));
+      const K = H(S);
+      return { A, key: K, proof: proof(username, salt, A, B, K) };
+    },
+    serverSession: (secret, A, salt, username, verifier, clientProof) => {
+      const B = pad(mod(k * toInt(verifier) + modPow(g, toInt(secret))));
+      const u = toInt(H(A, B));
+      const S = pad(modPow(toInt(A) * modPow(toInt(verifier), u), toInt(secret)));
+      const K = H(S);
+      if (clientProof !== proof(username, salt, A, B, K)) throw new Error('Client\'s proof is invalid');
+      return { key: K, proof: H(A, clientProof, K) };
+    },
+  };
+}
+
+test('6144- and 8192-bit groups should interoperate with the reference arithmetic', async t => {
+  for (const group of [SrpGroup.RFC5054_6144, SrpGroup.RFC5054_8192]) {
+    const reference = referenceSrp(groupParameters(group));
+    const client = new RustSrpClient(group);
+    const server = new RustSrpServer(group);
+
+    const salt = jsSrpClient.generateSalt();
+    const privateKey = jsSrpClient.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+    const verifier = client.deriveVerifier(privateKey);
+    compareHex(t, verifier, reference.deriveVerifier(privateKey), `Verifiers should match for group ${group}`);
+
+    // Rust client against the reference server
+    const clientEphemeral = client.generateEphemeral();
+    const referenceServerEphemeral = reference.generateEphemeral(verifier);
+    const clientSession = client.deriveSession(
+      clientEphemeral.secret,
+      referenceServerEphemeral.public,
+      salt,
+      TEST_USERNAME,
+      privateKey
+    );
+    const referenceServerSession = reference.serverSession(
+      referenceServerEphemeral.secret,
+      clientEphemeral.public,
+      salt,
+      TEST_USERNAME,
+      verifier,
+      clientSession.proof
+    );
+    t.notThrows(() => {
+      client.verifySession(clientEphemeral.public, clientSession, referenceServerSession.proof);
+    }, `Rust client should verify the reference server for group ${group}`);
+    t.is(clientSession.key, referenceServerSession.key);
+
+    // Reference client against the Rust server
+    const referenceSecret = crypto.randomBytes(32).toString('hex');
+    const serverEphemeral = server.generateEphemeral(verifier);
+    const referenceClientSession = reference.clientSession(
+      referenceSecret,
+      serverEphemeral.public,
+      salt,
+      TEST_USERNAME,
+      privateKey
+    );
+    const serverSession = server.deriveSession(
+      serverEphemeral.secret,
+      referenceClientSession.A,
+      salt,
+      TEST_USERNAME,
+      verifier,
+      referenceClientSession.proof
+    );
+    t.is(serverSession.key, referenceClientSession.key, `Session keys should match for group ${group}`);
+  }
+});
+
+// ===== Custom group tests =====
+
+// RFC 5054 1024-bit group, as a legacy deployment would configure it
+const N_1024_HEX = 'EEAF0AB9ADB38DD69C33F80AFA8FC5E86072618775FF3C0B9EA2314C9C256576D674DF7496EA81D3383B4813D692C6E0E0D5D8E250B98BE48E495C1D6089DAD15DC7D7B46154D6B6CE8EF4AD69B15D4982559B297BCF1885C529F566660E57EC68EDBC3C05726CC02FD4CBF4976EAA9AFD5138FE8376435B9FC61D2FC0EB06E3';
+
+// RFC 2409 Oakley group 2, which is not one of the RFC 5054 groups
+const OAKLEY_1024_HEX = 'FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE65381FFFFFFFFFFFFFFFF';
+
+test('Custom group should match the equivalent builtin group', async t => {
+  const group = new CustomGroup(N_1024_HEX, '02');
+  const client = new RustSrpClient(group);
+  const server = new RustSrpServer(SrpGroup.RFC5054_1024);
+
+  performSRPAuthentication(t, client, server, TEST_USERNAME, TEST_PASSWORD);
+});
+
+test('Custom group should authenticate with its own prime', async t => {
+  const group = new CustomGroup(OAKLEY_1024_HEX, '2');
+  compareHex(t, group.prime, OAKLEY_1024_HEX, 'Prime should round-trip');
+  compareHex(t, group.generator, '2', 'Generator should round-trip');
+
+  for (const hash of [SrpHash.SHA1, SrpHash.SHA256, SrpHash.SHA512]) {
+    const client = new RustSrpClient(group, { hash });
+    const server = new RustSrpServer(group, { hash });
+
+    performSRPAuthentication(t, client, server, TEST_USERNAME, TEST_PASSWORD);
+  }
+});
+
+testWithRng('Seeded constructors should accept custom groups', t => {
+  const group = new CustomGroup(OAKLEY_1024_HEX, '2');
+  const seeded = RustSrpClient.withTestRng(1, group);
+  t.is(seeded.generateSalt(), RustSrpClient.withTestRng(1, group).generateSalt());
+});
+
+test('Custom group should not interoperate with a different group', async t => {
+  const client = new RustSrpClient(new CustomGroup(OAKLEY_1024_HEX, '2'));
+  const server = new RustSrpServer(SrpGroup.RFC5054_1024);
+
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = client.deriveVerifier(privateKey);
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+
+  const error = t.throws(() => {
+    const clientSession = client.deriveSession(
+      clientEphemeral.secret,
+      serverEphemeral.public,
+      salt,
+      TEST_USERNAME,
+      privateKey
+    );
+    server.deriveSession(
+      serverEphemeral.secret,
+      clientEphemeral.public,
+      salt,
+      TEST_USERNAME,
+      verifier,
+      clientSession.proof
+    );
+  });
+
+  t.regex(error.message, /invalid|out of range/);
+});
+
+test('Invalid custom groups should be rejected', t => {
+  const N = BigInt('0x' + OAKLEY_1024_HEX);
+
+  t.throws(() => new CustomGroup((N + 1n).toString(16), '2'), { message: 'Custom group prime must be odd' });
+  t.throws(() => new CustomGroup(OAKLEY_1024_HEX, '1'), { message: 'Custom group generator is in a small subgroup' });
+  t.throws(() => new CustomGroup(OAKLEY_1024_HEX, '0'), { message: 'Custom group generator is invalid' });
+  t.throws(() => new CustomGroup(OAKLEY_1024_HEX, OAKLEY_1024_HEX), { message: 'Custom group generator is out of range' });
+  t.throws(() => new CustomGroup('not hex', '2'));
+});
+
+// Validate builtin groups against their advertised size
+function validateBuiltinGroups(t, groups) {
+  for (const [group, bits] of groups) {
+    const { prime, generator } = groupParameters(group);
+    const report = validateGroup(prime, generator, { minBits: 1024 });
+
+    t.true(report.valid, `${bits}-bit group should be valid: ${report.problems.join(', ')}`);
+    t.is(report.bits, bits);
+    t.true(report.isSafePrime);
+    t.true(report.generatorInRange);
+  }
+}
+
+test('Builtin groups should pass validation', t => {
+  validateBuiltinGroups(t, [
+    [SrpGroup.RFC5054_1024, 1024],
+    [SrpGroup.RFC5054_1536, 1536],
+    [SrpGroup.RFC5054_2048, 2048],
+    [SrpGroup.RFC5054_3072, 3072],
+    [SrpGroup.RFC5054_4096, 4096],
+  ]);
+
+  compareHex(t, groupParameters(SrpGroup.RFC5054_1024).prime, N_1024_HEX, 'Builtin constants should be exposed');
+});
+
+// Slow: 40 Miller-Rabin rounds on N and on (N - 1) / 2 at these sizes
+test('6144- and 8192-bit builtin groups should pass validation', t => {
+  t.timeout(10 * 60 * 1000);
+  validateBuiltinGroups(t, [
+    [SrpGroup.RFC5054_6144, 6144],
+    [SrpGroup.RFC5054_8192, 8192],
+  ]);
+});
+
+test('Group validation should report each weakness', t => {
+  // Valid safe prime, but below the default minimum size
+  const small = validateGroup(OAKLEY_1024_HEX, '2');
+  t.false(small.valid);
+  t.false(small.meetsMinimumBits);
+  t.true(small.isSafePrime);
+  t.deepEqual([...small.problems], ['N has 1024 bits, fewer than the required 2048']);
+
+  // Odd composite
+  const composite = validateGroup((BigInt('0x' + OAKLEY_1024_HEX) * 3n).toString(16), '2', { minBits: 1024 });
+  t.false(composite.isPrime);
+  t.false(composite.isSafePrime);
+  t.true(composite.problems.includes('N is not prime'));
+
+  // 2^127 - 1 is prime, but (N - 1) / 2 = 2^126 - 1 is not
+  const mersenne = validateGroup(((1n << 127n) - 1n).toString(16), '3', { minBits: 127 });
+  t.true(mersenne.isPrime);
+  t.false(mersenne.isSafePrime);
+  t.deepEqual([...mersenne.problems], ['N is not a safe prime, (N - 1) / 2 is not prime']);
+
+  // Generator of the order-2 subgroup, which the range check rules out
+  const N = BigInt('0x' + OAKLEY_1024_HEX);
+  const order2 = validateGroup(OAKLEY_1024_HEX, (N - 1n).toString(16), { minBits: 1024 });
+  t.true(order2.isSafePrime);
+  t.false(order2.generatorInRange);
+  t.deepEqual([...order2.problems], ['g is in a small subgroup']);
+
+  t.throws(() => validateGroup('not hex', '2'));
+});
+
+// ===== Hash function tests =====
+
+test('SHA-1 private key should match RFC 5054 test vector', t => {
+  const client = new RustSrpClient(SrpGroup.RFC5054_1024, { hash: SrpHash.SHA1 });
+  const privateKey = client.derivePrivateKey('BEB25379D1A8581EB5A727673A2441EE', 'alice', 'password123');
+
+  compareHex(t, privateKey, '94B7555AABE9127CC58CCF4993DB6CF84D16C124', 'x should match RFC 5054 Appendix B');
+});
+
+test('Default hash should be SHA-256', t => {
+  const salt = rustClient.generateSalt();
+  const sha256Client = new RustSrpClient(SrpGroup.RFC5054_2048, { hash: SrpHash.SHA256 });
+
+  t.is(
+    sha256Client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD),
+    rustClient.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD)
+  );
+});
+
+test('Every hash function should complete an authentication flow', async t => {
+  const hashes = [
+    [SrpHash.SHA1, 20],
+    [SrpHash.SHA256, 32],
+    [SrpHash.SHA384, 48],
+    [SrpHash.SHA512, 64],
+  ];
+
+  for (const [hash, outputBytes] of hashes) {
+    const client = new RustSrpClient(SrpGroup.RFC5054_2048, { hash });
+    const server = new RustSrpServer(SrpGroup.RFC5054_2048, { hash });
+
+    const { clientSession, serverSession } = performSRPAuthentication(
+      t, client, server, TEST_USERNAME, TEST_PASSWORD
+    );
+
+    t.is(clientSession.key.length, outputBytes * 2, 'Session key should be one hash output long');
+    t.is(serverSession.proof.length, outputBytes * 2, 'Server proof should be one hash output long');
+  }
+});
+
+test('Mixing different hash functions should fail', async t => {
+  const client = new RustSrpClient(SrpGroup.RFC5054_2048, { hash: SrpHash.SHA1 });
+  const server = new RustSrpServer(SrpGroup.RFC5054_2048, { hash: SrpHash.SHA512 });
+
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = client.deriveVerifier(privateKey);
+
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+
+  const clientSession = client.deriveSession(
+    clientEphemeral.secret,
+    serverEphemeral.public,
+    salt,
+    TEST_USERNAME,
+    privateKey
+  );
+
+  const error = t.throws(() => {
+    server.deriveSession(
+      serverEphemeral.secret,
+      clientEphemeral.public,
+      salt,
+      TEST_USERNAME,
+      verifier,
+      clientSession.proof
+    );
+  });
+
+  t.is(error.message, 'Client\'s proof is invalid');
+});
+
+// ===== Private key derivation function tests =====
+
+test('KDF parameters should round-trip through their encoding', t => {
+  const argon2 = encodeKdfParams({ algorithm: KdfAlgorithm.Argon2id });
+  t.is(argon2, '$argon2id$v=19$m=19456,t=2,p=1', 'Missing Argon2id parameters should use defaults');
+  t.is(encodeKdfParams({ algorithm: KdfAlgorithm.Scrypt, logN: 15 }), '$scrypt$ln=15,r=8,p=1');
+  t.is(encodeKdfParams({ algorithm: KdfAlgorithm.Pbkdf2, iterations: 1000 }), '$pbkdf2$i=1000');
+
+  const decoded = decodeKdfParams('$argon2id$v=19$m=65536,t=3,p=4');
+  t.is(decoded.algorithm, KdfAlgorithm.Argon2id);
+  t.is(decoded.memoryCost, 65536);
+  t.is(decoded.iterations, 3);
+  t.is(decoded.parallelism, 4);
+  t.is(encodeKdfParams(decoded), '$argon2id$v=19$m=65536,t=3,p=4');
+
+  for (const malformed of ['', 'argon2id', '$argon2id$m=1,t=1,p=1', '$scrypt$r=8,ln=15,p=1', '$pbkdf2$i=-1', '$bcrypt$c=10']) {
+    t.throws(() => decodeKdfParams(malformed), undefined, `${malformed} should be rejected`);
+  }
+
+  // Costs come from the server and are capped before anything is allocated
+  const client = new RustSrpClient();
+  for (const oversized of ['$argon2id$v=19$m=4294967295,t=1,p=1', '$scrypt$ln=40,r=8,p=1', '$pbkdf2$i=4294967295']) {
+    t.throws(() => decodeKdfParams(oversized), { code: SrpError.InvalidKdfParams });
+    t.throws(() => {
+      client.derivePrivateKeyWith(client.generateSalt(), TEST_USERNAME, TEST_PASSWORD, oversized);
+    }, { code: SrpError.InvalidKdfParams });
+  }
+  t.throws(() => {
+    client.derivePrivateKeyWith(client.generateSalt(), TEST_USERNAME, TEST_PASSWORD, { algorithm: KdfAlgorithm.Scrypt, blockSize: 1 << 20 });
+  }, { code: SrpError.InvalidKdfParams });
+});
+
+test('PBKDF2 and scrypt private keys should match node crypto', t => {
+  const client = new RustSrpClient();
+  const salt = client.generateSalt();
+  const saltBytes = Buffer.from(salt, 'hex');
+  const credentials = Buffer.from(`${TEST_USERNAME}:${TEST_PASSWORD}`);
+
+  // x = H(s, KDF(I | ':' | p, s))
+  const expectedX = stretched =>
+    crypto.createHash('sha256').update(saltBytes).update(stretched).digest('hex');
+
+  const pbkdf2 = crypto.pbkdf2Sync(credentials, saltBytes, 1000, 32, 'sha256');
+  compareHex(
+    t,
+    client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, '$pbkdf2$i=1000'),
+    expectedX(pbkdf2),
+    'PBKDF2 private key should match'
+  );
+
+  const scrypt = crypto.scryptSync(credentials, saltBytes, 32, { N: 1024, r: 8, p: 1 });
+  compareHex(
+    t,
+    client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, '$scrypt$ln=10,r=8,p=1'),
+    expectedX(scrypt),
+    'scrypt private key should match'
+  );
+});
+
+test('Argon2id private key should authenticate', async t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+  const params = { algorithm: KdfAlgorithm.Argon2id, memoryCost: 256, iterations: 1, parallelism: 1 };
+
+  // Registration: the server stores the encoded parameters next to the salt
+  const salt = client.generateSalt();
+  const kdf = encodeKdfParams(params);
+  const privateKey = client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, params);
+  const verifier = client.deriveVerifier(privateKey);
+
+  t.is(client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, kdf), privateKey, 'Encoded and object parameters should agree');
+  t.not(client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD), privateKey, 'Argon2id should change the private key');
+  t.not(
+    client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, { ...params, iterations: 2 }),
+    privateKey,
+    'Cost parameters should change the private key'
+  );
+
+  // Login: the client derives the key again from the stored parameters
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+  const clientSession = client.deriveSession(
+    clientEphemeral.secret,
+    serverEphemeral.public,
+    salt,
+    TEST_USERNAME,
+    client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, kdf)
+  );
+
+  t.notThrows(() => {
+    server.deriveSession(serverEphemeral.secret, clientEphemeral.public, salt, TEST_USERNAME, verifier, clientSession.proof);
+  });
+
+  t.throws(() => {
+    client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, { ...params, memoryCost: 1 });
+  }, { message: /Invalid KDF parameters/ });
+});
+
+const hexToBuffer = hex => Buffer.from(hex.length % 2 ? '0' + hex : hex, 'hex');
+
+function encodedHandshake(client, server) {
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = client.deriveVerifier(privateKey);
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+  const clientSession = client.deriveSession(
+    clientEphemeral.secret,
+    serverEphemeral.public,
+    salt,
+    TEST_USERNAME,
+    privateKey
+  );
+  const serverSession = server.deriveSession(
+    serverEphemeral.secret,
+    clientEphemeral.public,
+    salt,
+    TEST_USERNAME,
+    verifier,
+    clientSession.proof
+  );
+  client.verifySession(clientEphemeral.public, clientSession, serverSession.proof);
+
+  return { salt, privateKey, verifier, clientEphemeral, serverEphemeral, clientSession, serverSession };
+}
+
+test('Buffer encoding should return fixed-length values', t => {
+  const options = { encoding: SrpEncoding.Buffer };
+  const flow = encodedHandshake(new RustSrpClient(SrpGroup.RFC5054_2048, options), new RustSrpServer(SrpGroup.RFC5054_2048, options));
+
+  const lengths = {
+    salt: [flow.salt, 32],
+    privateKey: [flow.privateKey, 32],
+    verifier: [flow.verifier, 256],
+    clientSecret: [flow.clientEphemeral.secret, 32],
+    clientPublic: [flow.clientEphemeral.public, 256],
+    serverSecret: [flow.serverEphemeral.secret, 32],
+    serverPublic: [flow.serverEphemeral.public, 256],
+    clientKey: [flow.clientSession.key, 32],
+    clientProof: [flow.clientSession.proof, 32],
+    serverKey: [flow.serverSession.key, 32],
+    serverProof: [flow.serverSession.proof, 32],
+  };
+  for (const [name, [value, length]] of Object.entries(lengths)) {
+    t.true(Buffer.isBuffer(value), `${name} should be a Buffer`);
+    t.is(value.length, length, `${name} should be ${length} bytes`);
+  }
+  t.deepEqual(flow.clientSession.key, flow.serverSession.key);
+
+  // Lengths follow the hash output and the group size
+  const sha512 = encodedHandshake(
+    new RustSrpClient(SrpGroup.RFC5054_4096, { ...options, hash: SrpHash.SHA512 }),
+    new RustSrpServer(SrpGroup.RFC5054_4096, { ...options, hash: SrpHash.SHA512 })
+  );
+  t.is(sha512.privateKey.length, 64);
+  t.is(sha512.clientSession.proof.length, 64);
+  t.is(sha512.verifier.length, 512);
+  t.is(sha512.serverEphemeral.public.length, 512);
+});
+
+test('Base64 encoding should complete an authentication flow', t => {
+  const options = { encoding: SrpEncoding.Base64 };
+  const flow = encodedHandshake(new RustSrpClient(undefined, options), new RustSrpServer(undefined, options));
+
+  t.is(typeof flow.salt, 'string');
+  t.is(Buffer.from(flow.salt, 'base64').length, 32);
+  t.is(Buffer.from(flow.verifier, 'base64').length, 256);
+  t.is(Buffer.from(flow.clientEphemeral.public, 'base64').length, 256);
+  t.is(flow.clientSession.key, flow.serverSession.key);
+
+  const client = new RustSrpClient(undefined, options);
+  t.throws(() => {
+    client.deriveVerifier('not base64!');
+  }, { message: /Invalid/i });
+});
+
+testWithRng('Encodings should agree on the same values', t => {
+  const hexClient = RustSrpClient.withTestRng(7);
+  const bufferClient = RustSrpClient.withTestRng(7, undefined, { encoding: SrpEncoding.Buffer });
+  const base64Client = RustSrpClient.withTestRng(7, undefined, { encoding: SrpEncoding.Base64 });
+
+  const salt = hexClient.generateSalt();
+  const bufferSalt = bufferClient.generateSalt();
+  t.is(BigInt('0x' + salt), BigInt('0x' + bufferSalt.toString('hex')));
+  t.is(base64Client.generateSalt(), bufferSalt.toString('base64'));
+
+  const privateKey = hexClient.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = hexClient.deriveVerifier(privateKey);
+  const bufferVerifier = bufferClient.deriveVerifier(hexToBuffer(privateKey));
+  t.is(BigInt('0x' + bufferVerifier.toString('hex')), BigInt('0x' + verifier));
+
+  // Buffers are accepted in hex mode too
+  t.is(hexClient.deriveVerifier(hexToBuffer(privateKey)), verifier);
+});
+
+test('Peers using different encodings should authenticate', t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer(undefined, { encoding: SrpEncoding.Buffer });
+
+  // Registration with hex values, stored as bytes by the server
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = hexToBuffer(client.deriveVerifier(privateKey));
+
+  // The values exchanged over the wire are converted at each end
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+  const clientSession = client.deriveSession(
+    clientEphemeral.secret,
+    serverEphemeral.public.toString('hex'),
+    salt,
+    TEST_USERNAME,
+    privateKey
+  );
+  const serverSession = server.deriveSession(
+    serverEphemeral.secret,
+    hexToBuffer(clientEphemeral.public),
+    hexToBuffer(salt),
+    TEST_USERNAME,
+    verifier,
+    hexToBuffer(clientSession.proof)
+  );
+
+  t.is(BigInt('0x' + serverSession.key.toString('hex')), BigInt('0x' + clientSession.key));
+  t.notThrows(() => {
+    client.verifySession(clientEphemeral.public, clientSession, serverSession.proof.toString('hex'));
+  });
+});
+
+testWithRng('Async variants should match the synchronous results', async t => {
+  for (const options of [undefined, { encoding: SrpEncoding.Buffer }]) {
+    const client = new RustSrpClient(SrpGroup.RFC5054_4096, options);
+    const syncServer = RustSrpServer.withTestRng(11, SrpGroup.RFC5054_4096, options);
+    const asyncServer = RustSrpServer.withTestRng(11, SrpGroup.RFC5054_4096, options);
+
+    const salt = client.generateSalt();
+    const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+    const verifier = client.deriveVerifier(privateKey);
+    t.deepEqual(await client.deriveVerifierAsync(privateKey), verifier);
+
+    const clientEphemeral = client.generateEphemeral();
+    const serverEphemeral = syncServer.generateEphemeral(verifier);
+    const asyncServerEphemeral = await asyncServer.generateEphemeralAsync(verifier);
+    t.deepEqual(asyncServerEphemeral.secret, serverEphemeral.secret);
+    t.deepEqual(asyncServerEphemeral.public, serverEphemeral.public);
+
+    const args = [clientEphemeral.secret, serverEphemeral.public, salt, TEST_USERNAME, privateKey];
+    const clientSession = client.deriveSession(...args);
+    t.deepEqual(await client.deriveSessionAsync(...args), clientSession);
+
+    const serverArgs = [serverEphemeral.secret, clientEphemeral.public, salt, TEST_USERNAME, verifier, clientSession.proof];
+    const serverSession = syncServer.deriveSession(...serverArgs);
+    t.deepEqual(await asyncServer.deriveSessionAsync(...serverArgs), serverSession);
+
+    t.notThrows(() => {
+      client.verifySession(clientEphemeral.public, clientSession, serverSession.proof);
+    });
+  }
+});
+
+test('Async variants should reject like the synchronous ones', async t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = client.deriveVerifier(privateKey);
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+
+  await t.throwsAsync(
+    server.deriveSessionAsync(serverEphemeral.secret, clientEphemeral.public, salt, TEST_USERNAME, verifier, 'deadbeef'),
+    { message: 'Client\'s proof is invalid' }
+  );
+  await t.throwsAsync(
+    client.deriveSessionAsync(clientEphemeral.secret, '0', salt, TEST_USERNAME, privateKey),
+    { message: /Server's public ephemeral value/ }
+  );
+  await t.throwsAsync(server.generateEphemeralAsync('not hex'));
+});
+
+test('Async variants should not block the event loop', async t => {
+  const client = new RustSrpClient(SrpGroup.RFC5054_8192);
+  const privateKey = client.derivePrivateKey(client.generateSalt(), TEST_USERNAME, TEST_PASSWORD);
+
+  let ticked = false;
+  setImmediate(() => {
+    ticked = true;
+  });
+  const verifier = client.deriveVerifierAsync(privateKey);
+  t.false(ticked, 'The task should only start in the background');
+  await verifier;
+  t.true(ticked, 'The event loop should have run while the verifier was computed');
+});
+
+test('Server handshake should authenticate and hide its secret', t => {
+  for (const options of [undefined, { encoding: SrpEncoding.Buffer }]) {
+    const client = new RustSrpClient(undefined, options);
+    const server = new RustSrpServer(undefined, options);
+
+    const salt = client.generateSalt();
+    const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+    const verifier = client.deriveVerifier(privateKey);
+
+    const handshake = server.startHandshake(TEST_USERNAME, salt, verifier);
+    t.deepEqual(handshake.salt, salt);
+    t.deepEqual(Object.keys(handshake), [], 'No state should be exposed as properties');
+    t.is(handshake.secret, undefined);
+
+    const clientEphemeral = client.generateEphemeral();
+    const clientSession = client.deriveSession(clientEphemeral.secret, handshake.public, handshake.salt, TEST_USERNAME, privateKey);
+    const serverSession = handshake.finish(clientEphemeral.public, clientSession.proof);
+
+    t.deepEqual(serverSession.key, clientSession.key);
+    t.notThrows(() => {
+      client.verifySession(clientEphemeral.public, clientSession, serverSession.proof);
+    });
+  }
+});
+
+test('Server handshake should only be finished once', t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = client.deriveVerifier(privateKey);
+  const clientEphemeral = client.generateEphemeral();
+
+  const handshake = server.startHandshake(TEST_USERNAME, salt, verifier);
+  const clientSession = client.deriveSession(clientEphemeral.secret, handshake.public, salt, TEST_USERNAME, privateKey);
+  handshake.finish(clientEphemeral.public, clientSession.proof);
+
+  t.throws(() => {
+    handshake.finish(clientEphemeral.public, clientSession.proof);
+  }, { message: 'Handshake has already been finished' });
+
+  // A failed attempt consumes the handshake too
+  const failed = server.startHandshake(TEST_USERNAME, salt, verifier);
+  const session = client.deriveSession(clientEphemeral.secret, failed.public, salt, TEST_USERNAME, privateKey);
+  t.throws(() => {
+    failed.finish(clientEphemeral.public, 'deadbeef');
+  }, { message: 'Client\'s proof is invalid' });
+  t.throws(() => {
+    failed.finish(clientEphemeral.public, session.proof);
+  }, { message: 'Handshake has already been finished' });
+});
+
+test('Client and server handshakes should authenticate', t => {
+  for (const options of [undefined, { encoding: SrpEncoding.Base64 }]) {
+    const client = new RustSrpClient(undefined, options);
+    const server = new RustSrpServer(undefined, options);
+
+    const salt = client.generateSalt();
+    const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+    const verifier = client.deriveVerifier(privateKey);
+
+    for (const clientHandshake of [
+      client.startHandshake(TEST_USERNAME, TEST_PASSWORD),
+      client.startHandshakeWithPrivateKey(TEST_USERNAME, privateKey),
+    ]) {
+      t.deepEqual(Object.keys(clientHandshake), [], 'No state should be exposed as properties');
+
+      const serverHandshake = server.startHandshake(TEST_USERNAME, salt, verifier);
+      const proof = clientHandshake.computeProof(serverHandshake.salt, serverHandshake.public);
+      const serverSession = serverHandshake.finish(clientHandshake.public, proof);
+      const key = clientHandshake.finish(serverSession.proof);
+
+      t.deepEqual(key, serverSession.key);
+    }
+  }
+});
+
+testWithRng('Client handshake should match deriveSession', t => {
+  const client = RustSrpClient.withTestRng(5);
+  const replay = RustSrpClient.withTestRng(5);
+  const server = new RustSrpServer();
+  const params = { algorithm: KdfAlgorithm.Pbkdf2, iterations: 1000 };
+
+  const salt = client.generateSalt();
+  replay.generateSalt();
+  const privateKey = client.derivePrivateKeyWith(salt, TEST_USERNAME, TEST_PASSWORD, params);
+  const verifier = client.deriveVerifier(privateKey);
+  const serverEphemeral = server.generateEphemeral(verifier);
+
+  // Both clients draw the same a from the seeded RNG
+  const handshake = client.startHandshake(TEST_USERNAME, TEST_PASSWORD, encodeKdfParams(params));
+  const clientEphemeral = replay.generateEphemeral();
+  t.is(handshake.public, clientEphemeral.public);
+
+  const proof = handshake.computeProof(salt, serverEphemeral.public);
+  const clientSession = replay.deriveSession(clientEphemeral.secret, serverEphemeral.public, salt, TEST_USERNAME, privateKey);
+  t.is(proof, clientSession.proof);
+
+  const serverSession = server.deriveSession(serverEphemeral.secret, handshake.public, salt, TEST_USERNAME, verifier, proof);
+  t.is(handshake.finish(serverSession.proof), clientSession.key);
+});
+
+test('Client handshake should enforce its order and reject bad proofs', t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const verifier = client.deriveVerifier(client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD));
+
+  const handshake = client.startHandshake(TEST_USERNAME, TEST_PASSWORD);
+  t.throws(() => {
+    handshake.finish('deadbeef');
+  }, { message: 'Proof has not been computed yet' });
+
+  const serverHandshake = server.startHandshake(TEST_USERNAME, salt, verifier);
+  handshake.computeProof(salt, serverHandshake.public);
+  t.throws(() => {
+    handshake.computeProof(salt, serverHandshake.public);
+  }, { message: 'Proof has already been computed' });
+
+  t.throws(() => {
+    handshake.finish('deadbeef');
+  }, { message: 'Server\'s proof is invalid' });
+  t.throws(() => {
+    handshake.finish('deadbeef');
+  }, { message: 'Handshake has already been finished' });
+
+  const malicious = client.startHandshake(TEST_USERNAME, TEST_PASSWORD);
+  t.throws(() => {
+    malicious.computeProof(salt, '0');
+  }, { message: /Server's public ephemeral value/ });
+});
+
+test('Sealed server handshake should resume on another server', t => {
+  const key = crypto.randomBytes(32);
+
+  for (const options of [undefined, { encoding: SrpEncoding.Buffer }]) {
+    const client = new RustSrpClient(undefined, options);
+    const node1 = new RustSrpServer(undefined, options);
+    const node2 = new RustSrpServer(undefined, options);
+
+    const salt = client.generateSalt();
+    const verifier = client.deriveVerifier(client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD));
+
+    const handshake = node1.startHandshake(TEST_USERNAME, salt, verifier);
+    const token = handshake.seal(key);
+    t.regex(token, /^[A-Za-z0-9_-]+$/);
+    t.throws(() => {
+      handshake.seal(key);
+    }, { message: 'Handshake has already been finished' });
+
+    const clientHandshake = client.startHandshake(TEST_USERNAME, TEST_PASSWORD);
+    const proof = clientHandshake.computeProof(handshake.salt, handshake.public);
+
+    const resumed = node2.resumeHandshake(token, key, verifier);
+    t.deepEqual(resumed.public, handshake.public);
+    t.deepEqual(resumed.salt, salt);
+
+    const serverSession = resumed.finish(clientHandshake.public, proof);
+    t.deepEqual(clientHandshake.finish(serverSession.proof), serverSession.key);
+  }
+});
+
+test('Sealing under a bad key should leave the handshake usable', t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const verifier = client.deriveVerifier(client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD));
+  const handshake = server.startHandshake(TEST_USERNAME, salt, verifier);
+  t.throws(() => {
+    handshake.seal(crypto.randomBytes(16));
+  }, { code: SrpError.InvalidKey });
+
+  const clientHandshake = client.startHandshake(TEST_USERNAME, TEST_PASSWORD);
+  const proof = clientHandshake.computeProof(handshake.salt, handshake.public);
+  const serverSession = handshake.finish(clientHandshake.public, proof);
+  t.deepEqual(clientHandshake.finish(serverSession.proof), serverSession.key);
+});
+
+test('Tampered, expired or mismatched handshake tokens should be rejected', t => {
+  const key = crypto.randomBytes(32);
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const verifier = client.deriveVerifier(client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD));
+  const otherVerifier = client.deriveVerifier(client.derivePrivateKey(salt, TEST_USERNAME, 'other password'));
+  const token = server.startHandshake(TEST_USERNAME, salt, verifier).seal(key);
+
+  const bytes = Buffer.from(token, 'base64url');
+  for (const offset of [0, 1, 30, bytes.length - 1]) {
+    const tampered = Buffer.from(bytes);
+    tampered[offset] ^= 1;
+    t.throws(() => {
+      server.resumeHandshake(tampered.toString('base64url'), key, verifier);
+    }, { message: 'Invalid handshake token' });
+  }
+  t.throws(() => {
+    server.resumeHandshake(bytes.subarray(0, 40).toString('base64url'), key, verifier);
+  }, { message: 'Invalid handshake token' });
+  t.throws(() => {
+    server.resumeHandshake(token, crypto.randomBytes(32), verifier);
+  }, { message: 'Invalid handshake token' });
+  t.throws(() => {
+    server.resumeHandshake(token, key.subarray(0, 16), verifier);
+  }, { message: 'Handshake token key must be 32 bytes' });
+
+  t.throws(() => {
+    server.resumeHandshake(token, key, otherVerifier);
+  }, { message: 'Verifier does not match the handshake token' });
+  t.throws(() => {
+    new RustSrpServer(SrpGroup.RFC5054_3072).resumeHandshake(token, key, verifier);
+  }, { message: 'Handshake token was sealed for a different group or hash function' });
+  t.throws(() => {
+    new RustSrpServer(undefined, { hash: SrpHash.SHA512 }).resumeHandshake(token, key, verifier);
+  }, { message: 'Handshake token was sealed for a different group or hash function' });
+
+  const expired = server.startHandshake(TEST_USERNAME, salt, verifier).seal(key, 0);
+  t.throws(() => {
+    server.resumeHandshake(expired, key, verifier);
+  }, { message: 'Handshake token has expired' });
+});
+
+test('Sealed tokens should resume until they expire and carry an id to spend', t => {
+  const key = crypto.randomBytes(32);
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const verifier = client.deriveVerifier(client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD));
+  const handshake = server.startHandshake(TEST_USERNAME, salt, verifier);
+  t.is(handshake.tokenId, null);
+  const token = handshake.seal(key);
+
+  // A failed attempt does not consume the token: the same b and B come back
+  const first = server.resumeHandshake(token, key, verifier);
+  t.regex(first.tokenId, /^[0-9a-f]{32}$/);
+  t.throws(() => {
+    first.finish(client.generateEphemeral().public, 'deadbeef');
+  }, { code: SrpError.BadClientProof });
+
+  const second = server.resumeHandshake(token, key, verifier);
+  t.is(second.tokenId, first.tokenId);
+  t.is(second.public, first.public);
+
+  // Callers enforce single use by recording the id as spent
+  const spent = new Set([first.tokenId]);
+  t.true(spent.has(second.tokenId));
+
+  // Every seal gets a fresh id
+  const other = server.startHandshake(TEST_USERNAME, salt, verifier).seal(key);
+  t.false(spent.has(server.resumeHandshake(other, key, verifier).tokenId));
+});
+
+test('Decoy challenges should look like real ones and always fail', t => {
+  const key = crypto.randomBytes(32);
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const decoy = server.fakeUserChallenge('nobody@example.com', key);
+  const real = server.startHandshake(TEST_USERNAME, client.generateSalt(), client.deriveVerifier(
+    client.derivePrivateKey(client.generateSalt(), TEST_USERNAME, TEST_PASSWORD)
+  ));
+  t.is(decoy.salt.length, real.salt.length);
+  t.is(decoy.public.length, real.public.length);
+
+  // The salt is stable per username and key, the ephemeral is fresh
+  const again = server.fakeUserChallenge('nobody@example.com', key);
+  t.is(again.salt, decoy.salt);
+  t.not(again.public, decoy.public);
+  t.not(server.fakeUserChallenge('somebody@example.com', key).salt, decoy.salt);
+  t.not(server.fakeUserChallenge('nobody@example.com', crypto.randomBytes(32)).salt, decoy.salt);
+
+  // Whatever password the client tries, the proof is rejected like a wrong one
+  const clientHandshake = client.startHandshake('nobody@example.com', TEST_PASSWORD);
+  const proof = clientHandshake.computeProof(decoy.salt, decoy.public);
+  t.throws(() => {
+    decoy.finish(clientHandshake.public, proof);
+  }, { message: 'Client\'s proof is invalid' });
+  t.throws(() => {
+    decoy.finish(clientHandshake.public, proof);
+  }, { message: 'Handshake has already been finished' });
+
+  t.throws(() => {
+    server.fakeUserChallenge('nobody@example.com', key.subarray(0, 16));
+  }, { message: 'Server secret key must be at least 32 bytes' });
+});
+
+test('Sealed decoy challenges should resume without a verifier and fail like a wrong password', t => {
+  const key = crypto.randomBytes(32);
+  const decoyKey = crypto.randomBytes(32);
+  const client = new RustSrpClient();
+  const node1 = new RustSrpServer();
+  const node2 = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const verifier = client.deriveVerifier(client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD));
+
+  // What node 2 sees for a wrong password on a real account...
+  const realToken = node1.startHandshake(TEST_USERNAME, salt, verifier).seal(key);
+  const wrong = client.startHandshake(TEST_USERNAME, 'wrong password');
+  const wrongProof = wrong.computeProof(salt, node2.resumeHandshake(realToken, key, verifier).public);
+  const realError = t.throws(() => {
+    node2.resumeHandshake(realToken, key, verifier).finish(wrong.public, wrongProof);
+  });
+
+  // ...is what it sees for any password on an unknown user, with or without a verifier
+  const decoy = node1.fakeUserChallenge('nobody@example.com', decoyKey);
+  const decoyToken = decoy.seal(key);
+  for (const lookedUp of [undefined, null, verifier]) {
+    const resumed = node2.resumeHandshake(decoyToken, key, lookedUp);
+    t.is(resumed.public, decoy.public);
+    t.is(resumed.salt, decoy.salt);
+
+    const attempt = client.startHandshake('nobody@example.com', TEST_PASSWORD);
+    const proof = attempt.computeProof(resumed.salt, resumed.public);
+    const error = t.throws(() => {
+      resumed.finish(attempt.public, proof);
+    });
+    t.is(error.code, realError.code);
+    t.is(error.message, realError.message);
+  }
+
+  // A real token still needs its verifier
+  t.throws(() => {
+    node2.resumeHandshake(realToken, key);
+  }, { code: SrpError.TokenMismatch });
+});
+
+test('Challenge store should only accept each challenge once', t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+  const store = new ChallengeStore(server);
+
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = client.deriveVerifier(privateKey);
+  const clientEphemeral = client.generateEphemeral();
+
+  const challenge = store.issue(verifier);
+  t.regex(challenge.id, /^[0-9a-f]{32}$/);
+  t.is(store.size, 1);
+
+  const clientSession = client.deriveSession(clientEphemeral.secret, challenge.public, salt, TEST_USERNAME, privateKey);
+  const serverSession = store.deriveSession(challenge.id, clientEphemeral.public, salt, TEST_USERNAME, verifier, clientSession.proof);
+  t.is(serverSession.key, clientSession.key);
+  t.is(store.size, 0);
+
+  t.throws(() => {
+    store.deriveSession(challenge.id, clientEphemeral.public, salt, TEST_USERNAME, verifier, clientSession.proof);
+  }, { message: 'Challenge is unknown or has already been used' });
+
+  // A failed attempt consumes the challenge too
+  const failed = store.issue(verifier);
+  const session = client.deriveSession(clientEphemeral.secret, failed.public, salt, TEST_USERNAME, privateKey);
+  t.throws(() => {
+    store.deriveSession(failed.id, clientEphemeral.public, salt, TEST_USERNAME, verifier, 'deadbeef');
+  }, { message: 'Client\'s proof is invalid' });
+  t.throws(() => {
+    store.deriveSession(failed.id, clientEphemeral.public, salt, TEST_USERNAME, verifier, session.proof);
+  }, { message: 'Challenge is unknown or has already been used' });
+
+  // A challenge only answers for the verifier it was issued for
+  const other = client.deriveVerifier(client.derivePrivateKey(salt, 'bob', TEST_PASSWORD));
+  const mismatched = store.issue(verifier);
+  t.throws(() => {
+    store.deriveSession(mismatched.id, clientEphemeral.public, salt, 'bob', other, session.proof);
+  }, { message: 'Verifier does not match the challenge', code: 'ChallengeMismatch' });
+  t.is(store.size, 0);
+});
+
+test('Challenge store should expire and evict challenges', t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+  const verifier = client.deriveVerifier(client.derivePrivateKey(client.generateSalt(), TEST_USERNAME, TEST_PASSWORD));
+
+  const expiring = new ChallengeStore(server, { ttlSeconds: 0 });
+  const challenge = expiring.issue(verifier);
+  t.throws(() => {
+    expiring.deriveSession(challenge.id, client.generateEphemeral().public, '00', TEST_USERNAME, verifier, 'deadbeef');
+  }, { message: 'Challenge has expired' });
+  t.is(expiring.size, 0);
+
+  const bounded = new ChallengeStore(server, { capacity: 3 });
+  const ids = Array.from({ length: 5 }, () => bounded.issue(verifier).id);
+  t.is(bounded.size, 3);
+  for (const id of ids.slice(0, 2)) {
+    t.throws(() => {
+      bounded.deriveSession(id, client.generateEphemeral().public, '00', TEST_USERNAME, verifier, 'deadbeef');
+    }, { message: 'Challenge is unknown or has already been used' });
+  }
+  t.is(bounded.size, 3);
+
+  t.throws(() => {
+    new ChallengeStore(server, { capacity: 0 });
+  }, { message: 'Challenge store capacity must be at least 1' });
+});
+
+test('Errors should carry a stable code', async t => {
+  const client = new RustSrpClient();
+  const server = new RustSrpServer();
+
+  const salt = client.generateSalt();
+  const privateKey = client.derivePrivateKey(salt, TEST_USERNAME, TEST_PASSWORD);
+  const verifier = client.deriveVerifier(privateKey);
+  const clientEphemeral = client.generateEphemeral();
+  const serverEphemeral = server.generateEphemeral(verifier);
+
+  t.throws(() => {
+    client.deriveVerifier('not hex');
+  }, { code: SrpError.InvalidHex });
+  t.throws(() => {
+    new RustSrpClient(undefined, { encoding: SrpEncoding.Base64 }).deriveVerifier('!!');
+  }, { code: SrpError.InvalidBase64 });
+  t.throws(() => {
+    srpGroupFromValue(1000);
+  }, { code: SrpError.UnknownGroup });
+  t.throws(() => {
+    decodeKdfParams('$argon2id$');
+  }, { code: SrpError.InvalidKdfParams });
+  t.throws(() => {
+    client.deriveSession(clientEphemeral.secret, '0', salt, TEST_USERNAME, privateKey);
+  }, { code: SrpError.InvalidPublicEphemeral });
+  t.throws(() => {
+    server.deriveSession(serverEphemeral.secret, clientEphemeral.public, salt, TEST_USERNAME, verifier, 'deadbeef');
+  }, { code: 'BadClientProof' });
+  t.throws(() => {
+    server.fakeUserChallenge(TEST_USERNAME, Buffer.alloc(16));
+  }, { code: SrpError.InvalidKey });
+
+  const handshake = client.startHandshake(TEST_USERNAME, TEST_PASSWORD);
+  t.throws(() => {
+    handshake.finish('deadbeef');
+  }, { code: SrpError.HandshakeOutOfOrder });
+  handshake.computeProof(salt, server.startHandshake(TEST_USERNAME, salt, verifier).public);
+  t.throws(() => {
+    handshake.finish('deadbeef');
+  }, { code: SrpError.BadServerProof });
+  t.throws(() => {
+    handshake.finish('deadbeef');
+  }, { code: SrpError.HandshakeFinished });
+
+  const key = crypto.randomBytes(32);
+  const token = server.startHandshake(TEST_USERNAME, salt, verifier).seal(key);
+  t.throws(() => {
+    server.resumeHandshake(token, crypto.randomBytes(32), verifier);
+  }, { code: SrpError.InvalidToken });
+  t.throws(() => {
+    new RustSrpServer(SrpGroup.RFC5054_1024).resumeHandshake(token, key, verifier);
+  }, { code: SrpError.TokenMismatch });
+
+  const store = new ChallengeStore(server);
+  t.throws(() => {
+    store.deriveSession('unknown', clientEphemeral.public, salt, TEST_USERNAME, verifier, 'deadbeef');
+  }, { code: SrpError.UnknownChallenge });
+
+  const error = await t.throwsAsync(
+    server.deriveSessionAsync(serverEphemeral.secret, clientEphemeral.public, salt, TEST_USERNAME, verifier, 'deadbeef')
+  );
+  t.is(error.code, SrpError.BadClientProof);
+  t.is(error.message, 'Client\'s proof is invalid');
+  await t.throwsAsync(server.generateEphemeralAsync('not hex'), { code: SrpError.InvalidHex });
+});
+
 // ==================== Additional stress tests ====================
 
 test('Stress test: Multiple authentication cycles should succeed', async t => {
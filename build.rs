@@ -1,5 +1,23 @@
+#[cfg(feature = "napi")]
 extern crate napi_build;
 
 fn main() {
+  #[cfg(feature = "napi")]
   napi_build::setup();
+
+  // Generate the C header from the ffi module into OUT_DIR, leaving the
+  // source tree alone. A test checks include/srp.h against this copy.
+  #[cfg(feature = "ffi")]
+  {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    cbindgen::generate(&crate_dir)
+      .expect("Unable to generate the C header")
+      .write_to_file(std::path::Path::new(&out_dir).join("srp.h"));
+    // cbindgen parses the whole crate from src/lib.rs, and reads its config
+    // and the manifest
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+  }
 }
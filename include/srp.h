@@ -0,0 +1,323 @@
+/*
+ * C ABI of the SRP-6a library, generated by cbindgen from src/ffi.rs.
+ * Do not edit.
+ *
+ * Objects are opaque handles created by srp_*_new or srp_*_start_handshake
+ * and released with the matching srp_*_free. Every other function returns an
+ * SrpStatus, SRP_STATUS_OK (0) on success. Values are big-endian byte
+ * strings: inputs are a pointer and a length, outputs go to a caller-provided
+ * buffer whose capacity is passed in *out_len. On return *out_len holds the
+ * length of the value, and SRP_STATUS_BUFFER_TOO_SMALL means nothing was
+ * written and the call should be retried with a buffer of that size. Strings
+ * are NUL-terminated UTF-8.
+ *
+ * Pointers must be null or valid for the given length, handles must come from
+ * this library and not be used after they are freed, and a handle must not be
+ * used from two threads at once.
+ */
+
+#ifndef SRP_H
+#define SRP_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of every function taking a handle
+ */
+enum SrpStatus
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  SRP_STATUS_OK = 0,
+  SRP_STATUS_INVALID_HEX = 1,
+  SRP_STATUS_INVALID_BASE64 = 2,
+  SRP_STATUS_INVALID_PUBLIC_EPHEMERAL = 3,
+  SRP_STATUS_ZERO_SCRAMBLER = 4,
+  SRP_STATUS_BAD_CLIENT_PROOF = 5,
+  SRP_STATUS_BAD_SERVER_PROOF = 6,
+  SRP_STATUS_UNKNOWN_GROUP = 7,
+  SRP_STATUS_INVALID_GROUP = 8,
+  SRP_STATUS_INVALID_KDF_PARAMS = 9,
+  SRP_STATUS_INVALID_KEY = 10,
+  SRP_STATUS_INVALID_OPTION = 11,
+  SRP_STATUS_HANDSHAKE_FINISHED = 12,
+  SRP_STATUS_HANDSHAKE_OUT_OF_ORDER = 13,
+  SRP_STATUS_INVALID_TOKEN = 14,
+  SRP_STATUS_TOKEN_EXPIRED = 15,
+  SRP_STATUS_TOKEN_MISMATCH = 16,
+  SRP_STATUS_UNKNOWN_CHALLENGE = 17,
+  SRP_STATUS_CHALLENGE_EXPIRED = 18,
+  SRP_STATUS_CHALLENGE_MISMATCH = 19,
+  /**
+   * A required pointer is null
+   */
+  SRP_STATUS_NULL_POINTER = 100,
+  /**
+   * A string is not valid UTF-8
+   */
+  SRP_STATUS_INVALID_UTF8 = 101,
+  /**
+   * The output buffer is too small, `*out_len` holds the required size
+   */
+  SRP_STATUS_BUFFER_TOO_SMALL = 102,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum SrpStatus SrpStatus;
+#else
+typedef int32_t SrpStatus;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+/**
+ * Client-side SRP implementation
+ */
+typedef struct SrpClient SrpClient;
+
+/**
+ * Client side of a single handshake
+ */
+typedef struct SrpClientHandshake SrpClientHandshake;
+
+/**
+ * Server-side SRP implementation
+ */
+typedef struct SrpServer SrpServer;
+
+/**
+ * Server side of a single handshake
+ */
+typedef struct SrpServerHandshake SrpServerHandshake;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create a client for a builtin group (size in bits, 0 for 2048) and a
+ * hash function (digest size in bits, 0 for SHA-256)
+ *
+ * # Safety
+ *
+ * `out` must be valid for writes.
+ */
+SrpStatus srp_client_new(uint32_t group_bits, uint32_t hash_bits, struct SrpClient **out);
+
+/**
+ * Free a client; null is ignored
+ *
+ * # Safety
+ *
+ * `client` must be null or come from `srp_client_new`.
+ */
+void srp_client_free(struct SrpClient *client);
+
+/**
+ * Generate a random salt for password hashing
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_client_generate_salt(struct SrpClient *client, uint8_t *out, size_t *out_len);
+
+/**
+ * Derive the private key from user credentials
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_client_derive_private_key(struct SrpClient *client,
+                                        const uint8_t *salt,
+                                        size_t salt_len,
+                                        const char *username,
+                                        const char *password,
+                                        uint8_t *out,
+                                        size_t *out_len);
+
+/**
+ * Derive the password verifier from the private key
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_client_derive_verifier(struct SrpClient *client,
+                                     const uint8_t *private_key,
+                                     size_t private_key_len,
+                                     uint8_t *out,
+                                     size_t *out_len);
+
+/**
+ * Start a handshake from the user's password
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_client_start_handshake(struct SrpClient *client,
+                                     const char *username,
+                                     const char *password,
+                                     struct SrpClientHandshake **out);
+
+/**
+ * Client's public ephemeral value A, to send to the server
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_client_handshake_public(struct SrpClientHandshake *handshake,
+                                      uint8_t *out,
+                                      size_t *out_len);
+
+/**
+ * Derive the session from the user's salt and the server's public
+ * ephemeral value B, and write the client's proof M1 for the server
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_client_handshake_compute_proof(struct SrpClientHandshake *handshake,
+                                             const uint8_t *salt,
+                                             size_t salt_len,
+                                             const uint8_t *server_public,
+                                             size_t server_public_len,
+                                             uint8_t *out,
+                                             size_t *out_len);
+
+/**
+ * Verify the server's proof M2 and write the session key. The session is
+ * dropped whatever the outcome, except on null pointers and short buffers.
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_client_handshake_finish(struct SrpClientHandshake *handshake,
+                                      const uint8_t *server_proof,
+                                      size_t server_proof_len,
+                                      uint8_t *key_out,
+                                      size_t *key_out_len);
+
+/**
+ * Free a client handshake; null is ignored
+ *
+ * # Safety
+ *
+ * `handshake` must be null or come from `srp_client_start_handshake`.
+ */
+void srp_client_handshake_free(struct SrpClientHandshake *handshake);
+
+/**
+ * Create a server for a builtin group (size in bits, 0 for 2048) and a
+ * hash function (digest size in bits, 0 for SHA-256)
+ *
+ * # Safety
+ *
+ * `out` must be valid for writes.
+ */
+SrpStatus srp_server_new(uint32_t group_bits, uint32_t hash_bits, struct SrpServer **out);
+
+/**
+ * Free a server; null is ignored
+ *
+ * # Safety
+ *
+ * `server` must be null or come from `srp_server_new`.
+ */
+void srp_server_free(struct SrpServer *server);
+
+/**
+ * Start a handshake from the user's stored salt and verifier
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_server_start_handshake(struct SrpServer *server,
+                                     const char *username,
+                                     const uint8_t *salt,
+                                     size_t salt_len,
+                                     const uint8_t *verifier,
+                                     size_t verifier_len,
+                                     struct SrpServerHandshake **out);
+
+/**
+ * Start a handshake for an unknown user, indistinguishable from a real one
+ * and always failing at the proof. `key` is a server-wide secret of at
+ * least 32 bytes.
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_server_fake_user_challenge(struct SrpServer *server,
+                                         const char *username,
+                                         const uint8_t *key,
+                                         size_t key_len,
+                                         struct SrpServerHandshake **out);
+
+/**
+ * Server's public ephemeral value B, to send to the client
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_server_handshake_public(struct SrpServerHandshake *handshake,
+                                      uint8_t *out,
+                                      size_t *out_len);
+
+/**
+ * User's salt, to send to the client along with B
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_server_handshake_salt(struct SrpServerHandshake *handshake,
+                                    uint8_t *out,
+                                    size_t *out_len);
+
+/**
+ * Verify the client's proof M1, then write the session key and the
+ * server's proof M2 for the client. The session is dropped whatever the
+ * outcome, except on null pointers and short buffers.
+ *
+ * # Safety
+ *
+ * See the conventions at the top of srp.h.
+ */
+SrpStatus srp_server_handshake_finish(struct SrpServerHandshake *handshake,
+                                      const uint8_t *client_public,
+                                      size_t client_public_len,
+                                      const uint8_t *client_proof,
+                                      size_t client_proof_len,
+                                      uint8_t *key_out,
+                                      size_t *key_out_len,
+                                      uint8_t *proof_out,
+                                      size_t *proof_out_len);
+
+/**
+ * Free a server handshake; null is ignored
+ *
+ * # Safety
+ *
+ * `handshake` must be null or come from `srp_server_start_handshake` or
+ * `srp_server_fake_user_challenge`.
+ */
+void srp_server_handshake_free(struct SrpServerHandshake *handshake);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SRP_H */
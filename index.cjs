@@ -310,19 +310,31 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { generateSalt, derivePrivateKey, ClientEphemeral, Client, deriveVerifier, generateClientEphemeral, deriveClientSession, verifySession, SrpGroup, srpGroupFromValue, ServerEphemeral, Server, generateServerEphemeral, deriveServerSession } = nativeBinding
+const { generateSalt, derivePrivateKey, ClientEphemeral, Client, ClientHandshake, SrpEncoding, SrpError, KdfAlgorithm, encodeKdfParams, decodeKdfParams, deriveVerifier, generateClientEphemeral, deriveClientSession, verifySession, SrpGroup, SrpHash, CustomGroup, srpGroupFromValue, groupParameters, ServerEphemeral, Server, ChallengeStore, ServerHandshake, generateServerEphemeral, deriveServerSession, validateGroup } = nativeBinding
 
 module.exports.generateSalt = generateSalt
 module.exports.derivePrivateKey = derivePrivateKey
 module.exports.ClientEphemeral = ClientEphemeral
 module.exports.Client = Client
+module.exports.ClientHandshake = ClientHandshake
+module.exports.SrpEncoding = SrpEncoding
+module.exports.SrpError = SrpError
+module.exports.KdfAlgorithm = KdfAlgorithm
+module.exports.encodeKdfParams = encodeKdfParams
+module.exports.decodeKdfParams = decodeKdfParams
 module.exports.deriveVerifier = deriveVerifier
 module.exports.generateClientEphemeral = generateClientEphemeral
 module.exports.deriveClientSession = deriveClientSession
 module.exports.verifySession = verifySession
 module.exports.SrpGroup = SrpGroup
+module.exports.SrpHash = SrpHash
+module.exports.CustomGroup = CustomGroup
 module.exports.srpGroupFromValue = srpGroupFromValue
+module.exports.groupParameters = groupParameters
 module.exports.ServerEphemeral = ServerEphemeral
 module.exports.Server = Server
+module.exports.ChallengeStore = ChallengeStore
+module.exports.ServerHandshake = ServerHandshake
 module.exports.generateServerEphemeral = generateServerEphemeral
 module.exports.deriveServerSession = deriveServerSession
+module.exports.validateGroup = validateGroup
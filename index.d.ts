@@ -9,9 +9,89 @@ export declare function generateSalt(): string
 export declare function derivePrivateKey(salt: string, username: string, password: string): string
 /** Client's session key and proof */
 export interface ClientSession {
-  key: string
-  proof: string
+  key: string | Buffer
+  proof: string | Buffer
 }
+/** Encoding of salts, keys, ephemerals and proofs exchanged with JS */
+export const enum SrpEncoding {
+  /** Hex strings (default) */
+  Hex = 'hex',
+  /** Fixed-length, left-padded big-endian Buffers */
+  Buffer = 'buffer',
+  /** Base64 strings of the same fixed-length bytes */
+  Base64 = 'base64'
+}
+/** Stable codes of the errors thrown by this library, set as `err.code` */
+export const enum SrpError {
+  /** A value is not valid hex */
+  InvalidHex = 'InvalidHex',
+  /** A value is not valid base64 */
+  InvalidBase64 = 'InvalidBase64',
+  /** The peer's public ephemeral value is 0, out of range or in a small subgroup */
+  InvalidPublicEphemeral = 'InvalidPublicEphemeral',
+  /** The scrambling parameter u = H(A, B) is zero */
+  ZeroScrambler = 'ZeroScrambler',
+  /** The client's proof M1 is wrong, e.g. because of a wrong password */
+  BadClientProof = 'BadClientProof',
+  /** The server's proof M2 is wrong */
+  BadServerProof = 'BadServerProof',
+  /** No builtin group has the requested size */
+  UnknownGroup = 'UnknownGroup',
+  /** A custom group's prime or generator is unusable */
+  InvalidGroup = 'InvalidGroup',
+  /** KDF parameters are malformed, unsupported or out of range */
+  InvalidKdfParams = 'InvalidKdfParams',
+  /** A key has the wrong length */
+  InvalidKey = 'InvalidKey',
+  /** An option has an invalid value */
+  InvalidOption = 'InvalidOption',
+  /** A handshake was used after it had been finished */
+  HandshakeFinished = 'HandshakeFinished',
+  /** A handshake's steps were called out of order */
+  HandshakeOutOfOrder = 'HandshakeOutOfOrder',
+  /** A handshake token is malformed, tampered with or sealed under another key */
+  InvalidToken = 'InvalidToken',
+  /** A handshake token has expired */
+  TokenExpired = 'TokenExpired',
+  /** A handshake token belongs to another group, hash function or verifier */
+  TokenMismatch = 'TokenMismatch',
+  /** A challenge is unknown, evicted or already used */
+  UnknownChallenge = 'UnknownChallenge',
+  /** A challenge has expired */
+  ChallengeExpired = 'ChallengeExpired',
+  /** A challenge was issued for another verifier */
+  ChallengeMismatch = 'ChallengeMismatch'
+}
+/** Password hashing function used to stretch the credentials */
+export const enum KdfAlgorithm {
+  /** Argon2id (RFC 9106) */
+  Argon2id = 0,
+  /** scrypt (RFC 7914) */
+  Scrypt = 1,
+  /** PBKDF2 with HMAC over the instance's hash function (RFC 8018) */
+  Pbkdf2 = 2
+}
+/**
+ * Cost parameters for the private key derivation.
+ * Fields that do not apply to the algorithm are ignored, missing ones use defaults.
+ */
+export interface KdfParams {
+  algorithm: KdfAlgorithm
+  /** Argon2id memory cost in KiB */
+  memoryCost?: number
+  /** Argon2id passes or PBKDF2 iterations */
+  iterations?: number
+  /** Argon2id lanes or scrypt p */
+  parallelism?: number
+  /** scrypt log2(N) */
+  logN?: number
+  /** scrypt r */
+  blockSize?: number
+}
+/** Encode KDF parameters so the server can store them next to the salt */
+export declare function encodeKdfParams(params: KdfParams): string
+/** Decode KDF parameters produced by encodeKdfParams */
+export declare function decodeKdfParams(encoded: string): KdfParams
 /** Derive the password verifier from the private key */
 export declare function deriveVerifier(privateKey: string): string
 /** Generate client's ephemeral key pair */
@@ -31,52 +111,240 @@ export const enum SrpGroup {
   /** 3072-bit SRP group from RFC 5054 */
   RFC5054_3072 = 3,
   /** 4096-bit SRP group from RFC 5054 */
-  RFC5054_4096 = 4
+  RFC5054_4096 = 4,
+  /** 6144-bit SRP group from RFC 5054 */
+  RFC5054_6144 = 5,
+  /** 8192-bit SRP group from RFC 5054 */
+  RFC5054_8192 = 6
+}
+/** Hash function used for k, u, x, K and the session proofs */
+export const enum SrpHash {
+  /** SHA-1, as used by RFC 5054 (TLS-SRP) */
+  SHA1 = 0,
+  /** SHA-256 */
+  SHA256 = 1,
+  /** SHA-384 */
+  SHA384 = 2,
+  /** SHA-512, as used by Apple-style SRP */
+  SHA512 = 3
+}
+/** Options for Client and Server instances */
+export interface SrpOptions {
+  /** Hash function (defaults to SHA-256) */
+  hash?: SrpHash
+  /** Encoding of values passed to and returned from methods (defaults to hex) */
+  encoding?: SrpEncoding
 }
 /** Helper function to create SrpGroup from bit size */
 export declare function srpGroupFromValue(value: number): SrpGroup
+/** Hex-encoded N and g of a group */
+export interface GroupParameters {
+  prime: string
+  generator: string
+}
+/** N and g of a builtin group, e.g. to validate them or share them with peers */
+export declare function groupParameters(group: SrpGroup): GroupParameters
 /** Server's session key and proof */
 export interface ServerSession {
-  key: string
-  proof: string
+  key: string | Buffer
+  proof: string | Buffer
+}
+/** Options for a ChallengeStore */
+export interface ChallengeStoreOptions {
+  /** Seconds a challenge can be answered for (defaults to 60) */
+  ttlSeconds?: number
+  /**
+   * Maximum number of outstanding challenges; the least recently issued
+   * ones are evicted first (defaults to 10000)
+   */
+  capacity?: number
+}
+/** Server's public ephemeral value, with the id it is stored under */
+export interface ServerChallenge {
+  id: string
+  public: string | Buffer
 }
 /** Generate server's ephemeral key pair */
 export declare function generateServerEphemeral(verifier: string): ServerEphemeral
 /** Derive the session key and proof on the server side */
 export declare function deriveServerSession(serverSecretEphemeral: string, clientPublicEphemeral: string, salt: string, username: string, verifier: string, clientSessionProof: string): ServerSession
+/** Options for validateGroup */
+export interface ValidateGroupOptions {
+  /** Minimum bit length of N (defaults to 2048) */
+  minBits?: number
+}
+/** Result of validating a group's N and g */
+export interface GroupReport {
+  /** Whether every check passed */
+  valid: boolean
+  /** Bit length of N */
+  bits: number
+  /** N is at least the minimum bit length */
+  meetsMinimumBits: boolean
+  /** N is a probable prime */
+  isPrime: boolean
+  /** N is a probable prime and so is (N - 1) / 2 */
+  isSafePrime: boolean
+  /**
+   * g lies in [2, N - 2]. With N a safe prime this is enough for g to
+   * generate a subgroup of order (N - 1) / 2 or N - 1
+   */
+  generatorInRange: boolean
+  /** Human-readable description of every failed check */
+  problems: Array<string>
+}
+/** Check that N is a large safe prime and that g generates a large subgroup */
+export declare function validateGroup(prime: string, generator: string, options?: ValidateGroupOptions | undefined | null): GroupReport
 /** Client's ephemeral key pair */
 export declare class ClientEphemeral {
-  secret: string
-  public: string
+  secret: string | Buffer
+  public: string | Buffer
 }
 /** Client-side SRP implementation */
 export declare class Client {
-  /** Create a new Client instance with optional parameter group */
-  constructor(group?: SrpGroup | undefined | null)
+  /** Create a new Client instance with optional parameter group (builtin or custom) and options */
+  constructor(group?: SrpGroup | CustomGroup | undefined | null, options?: SrpOptions | undefined | null)
   /** Generate a random salt for password hashing */
-  generateSalt(): string
+  generateSalt(): string | Buffer
   /** Derive the private key from user credentials */
-  derivePrivateKey(salt: string, username: string, password: string): string
+  derivePrivateKey(salt: string | Buffer, username: string, password: string): string | Buffer
+  /**
+   * Derive the private key with a password hashing function, given as
+   * KdfParams or in the form produced by encodeKdfParams
+   */
+  derivePrivateKeyWith(salt: string | Buffer, username: string, password: string, params: string | KdfParams): string | Buffer
   /** Derive the password verifier from the private key */
-  deriveVerifier(privateKey: string): string
+  deriveVerifier(privateKey: string | Buffer): string | Buffer
+  /** Derive the password verifier on the libuv thread pool */
+  deriveVerifierAsync(privateKey: string | Buffer): Promise<string | Buffer>
   /** Generate client's ephemeral key pair */
   generateEphemeral(): ClientEphemeral
   /** Derive the session key and proof on the client side */
-  deriveSession(clientSecretEphemeral: string, serverPublicEphemeral: string, salt: string, username: string, privateKey: string, clientPublicEphemeral?: string | undefined | null): ClientSession
+  deriveSession(clientSecretEphemeral: string | Buffer, serverPublicEphemeral: string | Buffer, salt: string | Buffer, username: string, privateKey: string | Buffer, clientPublicEphemeral?: string | Buffer | undefined | null): ClientSession
+  /** Derive the session key and proof on the libuv thread pool */
+  deriveSessionAsync(clientSecretEphemeral: string | Buffer, serverPublicEphemeral: string | Buffer, salt: string | Buffer, username: string, privateKey: string | Buffer, clientPublicEphemeral?: string | Buffer | undefined | null): Promise<ClientSession>
   /** Verify the server's session proof */
-  verifySession(clientPublicEphemeral: string, clientSession: ClientSession, serverSessionProof: string): void
+  verifySession(clientPublicEphemeral: string | Buffer, clientSession: ClientSession, serverSessionProof: string | Buffer): void
+  /**
+   * Start a handshake from the user's password, optionally stretched with
+   * KdfParams as in derivePrivateKeyWith. It keeps the secret ephemeral and
+   * the session to itself.
+   */
+  startHandshake(username: string, password: string, params?: string | KdfParams | undefined | null): ClientHandshake
+  /** Start a handshake from a private key derived at registration */
+  startHandshakeWithPrivateKey(username: string, privateKey: string | Buffer): ClientHandshake
+}
+/** Client side of a single handshake, created by Client.startHandshake */
+export declare class ClientHandshake {
+  /** Client's public ephemeral value A, to send to the server */
+  get public(): string | Buffer
+  /**
+   * Derive the session from the user's salt and the server's public
+   * ephemeral value B, and return the client's proof M1 for the server
+   */
+  computeProof(salt: string | Buffer, serverPublicEphemeral: string | Buffer): string | Buffer
+  /**
+   * Verify the server's proof M2 and return the session key.
+   * The session is dropped whatever the outcome.
+   */
+  finish(serverSessionProof: string | Buffer): string | Buffer
+}
+/** SRP group with a caller-supplied prime N and generator g */
+export declare class CustomGroup {
+  /**
+   * Create a group from a hex-encoded prime N and generator g.
+   * k and H(N) XOR H(g) are computed once here for every hash function.
+   */
+  constructor(prime: string, generator: string)
+  /** Hex-encoded prime N */
+  get prime(): string
+  /** Hex-encoded generator g */
+  get generator(): string
 }
 /** Server's ephemeral key pair */
 export declare class ServerEphemeral {
-  secret: string
-  public: string
+  secret: string | Buffer
+  public: string | Buffer
 }
 /** Server-side SRP implementation */
 export declare class Server {
-  /** Create a new Server instance with optional parameter group */
-  constructor(group?: SrpGroup | undefined | null)
+  /** Create a new Server instance with optional parameter group (builtin or custom) and options */
+  constructor(group?: SrpGroup | CustomGroup | undefined | null, options?: SrpOptions | undefined | null)
   /** Generate server's ephemeral key pair */
-  generateEphemeral(verifier: string): ServerEphemeral
+  generateEphemeral(verifier: string | Buffer): ServerEphemeral
+  /** Generate server's ephemeral key pair on the libuv thread pool */
+  generateEphemeralAsync(verifier: string | Buffer): Promise<ServerEphemeral>
+  /**
+   * Start a handshake that keeps the secret ephemeral and the user record
+   * to itself, for a single call to finish()
+   */
+  startHandshake(username: string, salt: string | Buffer, verifier: string | Buffer): ServerHandshake
+  /**
+   * Start a decoy handshake for a username that has no account. The salt
+   * and verifier are derived from an HMAC of the username under a server
+   * secret key of at least 32 bytes, so repeated probes see the same salt.
+   * finish() does the same work as for a real user and always fails.
+   */
+  fakeUserChallenge(username: string, serverSecretKey: Buffer): ServerHandshake
+  /**
+   * Resume a handshake sealed by ServerHandshake.seal, possibly on another
+   * node sharing the same key. The verifier is looked up again and must be
+   * the one the handshake was started with; omit it for a username with
+   * no account. A token sealed from a decoy resumes either way, and its
+   * finish() fails like a wrong password. A token resumes as often as it
+   * is presented until it expires; record the handshake's tokenId as
+   * spent to allow a single attempt.
+   */
+  resumeHandshake(token: string, key: Buffer, verifier?: string | Buffer | undefined | null): ServerHandshake
   /** Derive the session key and proof on the server side */
-  deriveSession(serverSecretEphemeral: string, clientPublicEphemeral: string, salt: string, username: string, verifier: string, clientSessionProof: string): ServerSession
+  deriveSession(serverSecretEphemeral: string | Buffer, clientPublicEphemeral: string | Buffer, salt: string | Buffer, username: string, verifier: string | Buffer, clientSessionProof: string | Buffer): ServerSession
+  /** Derive the session key and proof on the libuv thread pool */
+  deriveSessionAsync(serverSecretEphemeral: string | Buffer, clientPublicEphemeral: string | Buffer, salt: string | Buffer, username: string, verifier: string | Buffer, clientSessionProof: string | Buffer): Promise<ServerSession>
+}
+/**
+ * In-process store of server ephemerals. Each one expires after a TTL and
+ * is consumed by the first deriveSession attempt, successful or not.
+ */
+export declare class ChallengeStore {
+  /**
+   * Create a store issuing ephemerals with the server's group, hash
+   * function and encoding
+   */
+  constructor(server: Server, options?: ChallengeStoreOptions | undefined | null)
+  /** Number of challenges issued and not yet consumed or evicted */
+  get size(): number
+  /** Generate a server ephemeral and keep its secret half in the store */
+  issue(verifier: string | Buffer): ServerChallenge
+  /**
+   * Derive the session key and proof for an issued challenge. The
+   * challenge is consumed whatever the outcome.
+   */
+  deriveSession(id: string, clientPublicEphemeral: string | Buffer, salt: string | Buffer, username: string, verifier: string | Buffer, clientSessionProof: string | Buffer): ServerSession
+}
+/** Server side of a single handshake, created by Server.startHandshake */
+export declare class ServerHandshake {
+  /** Server's public ephemeral value B, to send to the client */
+  get public(): string | Buffer
+  /** User's salt, to send to the client */
+  get salt(): string | Buffer
+  /**
+   * Id of the sealed token this handshake was resumed from, in hex, or
+   * null if it was started on this server. Tokens can be resumed more
+   * than once, so record the id to refuse a second attempt.
+   */
+  get tokenId(): string | null
+  /**
+   * Seal the handshake into an opaque token, encrypted and authenticated
+   * under a 32-byte server key, to finish it with Server.resumeHandshake.
+   * The token expires after ttlSeconds (defaults to 60), and this
+   * handshake can no longer be finished. Until it expires, the token can
+   * be resumed any number of times.
+   */
+  seal(key: Buffer, ttlSeconds?: number | undefined | null): string
+  /**
+   * Verify the client's proof and derive the session key and proof.
+   * The secret ephemeral is dropped whatever the outcome, so a handshake
+   * can only be finished once.
+   */
+  finish(clientPublicEphemeral: string | Buffer, clientSessionProof: string | Buffer): ServerSession
 }
@@ -1,294 +1,451 @@
-use crate::params::{get_group_params, get_h_N_xor_h_g, H_str, SrpGroup, H, HASH_OUTPUT_BYTES};
-use crate::srp_integer::SrpInteger;
-use napi::bindgen_prelude::*;
-use napi_derive::napi;
-
-/// Generate a random salt for password hashing
-#[napi]
-pub fn generate_salt() -> String {
-  // s    User's salt
-  let s = SrpInteger::random_integer(HASH_OUTPUT_BYTES);
-  s.to_hex()
-}
-
-/// Derive the private key from user credentials
-#[napi]
-pub fn derive_private_key(salt: String, username: String, password: String) -> Result<String> {
-  // s    User's salt
-  // I    Username
-  // p    Cleartext Password
-  let s = SrpInteger::from_hex(&salt).map_err(|e| Error::new(Status::InvalidArg, e))?;
-  let I = username;
-  let p = password;
-
-  // x = H(s, H(I | ':' | p))
-  let i_p = format!("{}:{}", I, p);
-  let h_i_p = H_str(&i_p);
-  let x = H(&[&s, &h_i_p]);
-
-  Ok(x.to_hex())
-}
+use crate::error::{Error, Result, SrpError};
+use crate::kdf::{stretch, KdfParams};
+use crate::params::{
+  check_public_ephemeral, proof_matches, Group, H_str, SrpHash, SrpVariant, H, SECRET_BYTES,
+};
+use crate::rng::{RngSource, SrpRng};
+use crate::srp_integer::{SecretInteger, SrpInteger};
+use zeroize::Zeroizing;
 
 /// Client's ephemeral key pair
-#[napi]
 pub struct ClientEphemeral {
-  pub secret: String,
-  pub public: String,
+  pub secret: Zeroizing<Vec<u8>>,
+  pub public: Vec<u8>,
 }
 
 /// Client's session key and proof
-#[napi(object)]
 pub struct ClientSession {
-  pub key: String,
-  pub proof: String,
+  pub key: Zeroizing<Vec<u8>>,
+  pub proof: Vec<u8>,
 }
 
-/// Client-side SRP implementation
-#[napi]
+/// Client-side SRP implementation.
+/// Every value is a big-endian byte string; outputs are left-padded to a
+/// fixed length: 32 bytes for salts and secret ephemerals, the hash output
+/// size for private keys, session keys and proofs, and the size of N for
+/// verifiers and public ephemerals.
+#[derive(Clone)]
 pub struct Client {
-  group: SrpGroup,
+  pub(crate) group: Group,
+  pub(crate) hash: SrpHash,
+  pub(crate) variant: SrpVariant,
+  pub(crate) rng: RngSource,
+}
+
+impl Default for Client {
+  fn default() -> Self {
+    Client::new(Group::default(), SrpHash::default())
+  }
 }
 
-#[napi]
 impl Client {
-  /// Create a new Client instance with optional parameter group
-  #[napi(constructor)]
-  pub fn new(group: Option<SrpGroup>) -> Self {
+  /// Create a Client for a parameter group and hash function, drawing salts
+  /// and ephemerals from the OS CSPRNG
+  pub fn new(group: impl Into<Group>, hash: SrpHash) -> Self {
     Client {
-      group: group.unwrap_or_default(),
+      group: group.into(),
+      hash,
+      variant: SrpVariant::default(),
+      rng: RngSource::os(),
     }
   }
 
+  /// Draw salts and ephemerals from another source, e.g. a seeded DRBG in tests
+  pub fn with_rng(self, rng: impl SrpRng + 'static) -> Self {
+    Client {
+      rng: RngSource::new(rng),
+      ..self
+    }
+  }
+
+  /// Derive k as another flavor of SRP-6a, e.g. RFC 5054
+  pub fn with_variant(self, variant: SrpVariant) -> Self {
+    Client { variant, ..self }
+  }
+
+  /// Parameter group
+  pub fn group(&self) -> &Group {
+    &self.group
+  }
+
+  /// Hash function
+  pub fn hash(&self) -> SrpHash {
+    self.hash
+  }
+
+  /// Flavor of SRP-6a
+  pub fn variant(&self) -> SrpVariant {
+    self.variant
+  }
+
   /// Generate a random salt for password hashing
-  #[napi]
-  pub fn generate_salt(&self) -> String {
-    generate_salt()
+  pub fn generate_salt(&self) -> Vec<u8> {
+    self.salt().to_padded_bytes(SECRET_BYTES)
   }
 
   /// Derive the private key from user credentials
-  #[napi]
   pub fn derive_private_key(
     &self,
-    salt: String,
-    username: String,
-    password: String,
-  ) -> Result<String> {
-    derive_private_key(salt, username, password)
+    salt: &[u8],
+    username: &str,
+    password: &str,
+  ) -> Zeroizing<Vec<u8>> {
+    let s = SrpInteger::from_bytes(salt);
+    let x = self
+      .private_key(&s, username, password, None)
+      .expect("Only KDF parameters can be invalid");
+    Zeroizing::new(x.to_padded_bytes(self.hash.output_bytes()))
+  }
+
+  /// Derive the private key with a password hashing function
+  pub fn derive_private_key_with(
+    &self,
+    salt: &[u8],
+    username: &str,
+    password: &str,
+    params: &KdfParams,
+  ) -> Result<Zeroizing<Vec<u8>>> {
+    let s = SrpInteger::from_bytes(salt);
+    let x = self.private_key(&s, username, password, Some(params))?;
+    Ok(Zeroizing::new(x.to_padded_bytes(self.hash.output_bytes())))
   }
 
   /// Derive the password verifier from the private key
-  #[napi]
-  pub fn derive_verifier(&self, private_key: String) -> Result<String> {
+  pub fn derive_verifier(&self, private_key: &[u8]) -> Vec<u8> {
+    let x = SecretInteger::new(SrpInteger::from_bytes(private_key));
+    self.verifier(&x).to_padded_bytes(self.group.prime_bytes())
+  }
+
+  /// Generate client's ephemeral key pair
+  pub fn generate_ephemeral(&self) -> ClientEphemeral {
+    let (a, A) = self.ephemeral();
+    ClientEphemeral {
+      secret: Zeroizing::new(a.to_padded_bytes(SECRET_BYTES)),
+      public: A.to_padded_bytes(self.group.prime_bytes()),
+    }
+  }
+
+  /// Derive the session key and proof on the client side
+  pub fn derive_session(
+    &self,
+    client_secret_ephemeral: &[u8],
+    server_public_ephemeral: &[u8],
+    salt: &[u8],
+    username: &str,
+    private_key: &[u8],
+  ) -> Result<ClientSession> {
     // N    A large safe prime
     // g    A generator modulo N
-    let (N, g, _) = get_group_params(self.group);
+    let (N, g) = self.group.constants();
 
-    // x    Private key (derived from password and salt)
-    let x = SrpInteger::from_hex(&private_key).map_err(|e| Error::new(Status::InvalidArg, e))?;
+    let a = SecretInteger::new(SrpInteger::from_bytes(client_secret_ephemeral));
+    let A = g.mod_pow(&a, N);
+    let B = SrpInteger::from_bytes(server_public_ephemeral);
+    let s = SrpInteger::from_bytes(salt);
+    let x = SecretInteger::new(SrpInteger::from_bytes(private_key));
 
-    // v = g^x (password verifier)
-    let v = g.mod_pow(&x, N);
+    let (K, M1) = self.session(&a, &A, &B, &s, username, &x)?;
 
-    Ok(v.to_hex())
+    Ok(ClientSession {
+      key: Zeroizing::new(K.to_padded_bytes(self.hash.output_bytes())),
+      proof: M1.to_padded_bytes(self.hash.output_bytes()),
+    })
   }
 
-  /// Generate client's ephemeral key pair
-  #[napi]
-  pub fn generate_ephemeral(&self) -> ClientEphemeral {
+  /// Verify the server's session proof
+  pub fn verify_session(
+    &self,
+    client_public_ephemeral: &[u8],
+    client_session: &ClientSession,
+    server_session_proof: &[u8],
+  ) -> Result<()> {
+    let A = SrpInteger::from_bytes(client_public_ephemeral);
+    let M1 = SrpInteger::from_bytes(&client_session.proof);
+    let K = SecretInteger::new(SrpInteger::from_bytes(&client_session.key));
+
+    self.check_server_proof(&A, &M1, &K, server_session_proof)
+  }
+
+  /// Start a handshake from the user's password, optionally stretched with
+  /// KDF parameters as in derive_private_key_with. It keeps the secret
+  /// ephemeral and the session to itself.
+  pub fn start_handshake(
+    &self,
+    username: &str,
+    password: &str,
+    params: Option<&KdfParams>,
+  ) -> ClientHandshake {
+    self.handshake(
+      username.to_string(),
+      Credentials::Password(Zeroizing::new(password.to_string()), params.cloned()),
+    )
+  }
+
+  /// Start a handshake from a private key derived at registration
+  pub fn start_handshake_with_private_key(
+    &self,
+    username: &str,
+    private_key: &[u8],
+  ) -> ClientHandshake {
+    let x = SecretInteger::new(SrpInteger::from_bytes(private_key));
+    self.handshake(username.to_string(), Credentials::PrivateKey(x))
+  }
+}
+
+impl Client {
+  // s    User's salt
+  pub(crate) fn salt(&self) -> SrpInteger {
+    SrpInteger::random_integer(&self.rng, SECRET_BYTES)
+  }
+
+  // x = H(s, H(I | ':' | p)), or H(s, KDF(I | ':' | p, s)) given KDF parameters
+  pub(crate) fn private_key(
+    &self,
+    s: &SrpInteger,
+    I: &str,
+    p: &str,
+    params: Option<&KdfParams>,
+  ) -> Result<SecretInteger> {
+    let i_p = Zeroizing::new(format!("{}:{}", I, p));
+    let h_i_p = match params {
+      Some(params) => {
+        let stretched = stretch(self.hash, params, i_p.as_bytes(), &s.to_bytes())?;
+        SecretInteger::new(SrpInteger::from_bytes(&stretched))
+      }
+      None => SecretInteger::new(H_str(self.hash, &i_p)),
+    };
+    Ok(SecretInteger::new(H(self.hash, &[s, &h_i_p])))
+  }
+
+  // v = g^x (password verifier)
+  pub(crate) fn verifier(&self, x: &SecretInteger) -> SrpInteger {
+    // N    A large safe prime
+    // g    A generator modulo N
+    let (N, g) = self.group.constants();
+
+    g.mod_pow(x, N)
+  }
+
+  // a (random) and A = g^a
+  pub(crate) fn ephemeral(&self) -> (SecretInteger, SrpInteger) {
     // N    A large safe prime
     // g    A generator modulo N
-    let (N, g, _) = get_group_params(self.group);
+    let (N, g) = self.group.constants();
 
     // a    Secret ephemeral value
-    let a = SrpInteger::random_integer(HASH_OUTPUT_BYTES);
+    let a = SecretInteger::random_integer(&self.rng, SECRET_BYTES);
 
     // A = g^a (public ephemeral value)
     let A = g.mod_pow(&a, N);
 
-    ClientEphemeral {
-      secret: a.to_hex(),
-      public: A.to_hex(),
-    }
+    (a, A)
   }
 
-  /// Derive the session key and proof on the client side
-  #[napi]
-  pub fn derive_session(
+  // Session key K and client proof M1 for the client's a and A and the server's B
+  pub(crate) fn session(
     &self,
-    client_secret_ephemeral: String,
-    server_public_ephemeral: String,
-    salt: String,
-    username: String,
-    private_key: String,
-    client_public_ephemeral: Option<String>,
-  ) -> Result<ClientSession> {
+    a: &SecretInteger,
+    A: &SrpInteger,
+    B: &SrpInteger,
+    s: &SrpInteger,
+    I: &str,
+    x: &SecretInteger,
+  ) -> Result<(SecretInteger, SrpInteger)> {
     // N    A large safe prime
     // g    A generator modulo N
-    // k    Multiplier parameter (k = H(N, g))
-    let (N, g, k) = get_group_params(self.group);
-
-    // a    Secret ephemeral value
-    let a = SrpInteger::from_hex(&client_secret_ephemeral)
-      .map_err(|e| Error::new(Status::InvalidArg, e))?;
-
-    // A    Public ephemeral value
-    let A = match client_public_ephemeral {
-      Some(A_str) => SrpInteger::from_hex(&A_str).map_err(|e| Error::new(Status::InvalidArg, e))?,
-      None => g.mod_pow(&a, N),
-    };
-
-    // B    Server's public ephemeral value
-    let B = SrpInteger::from_hex(&server_public_ephemeral)
-      .map_err(|e| Error::new(Status::InvalidArg, e))?;
+    // k    Multiplier parameter (k = H(N, g), or H(N | PAD(g)) in RFC 5054)
+    let (N, g, k) = self.group.params(self.hash, self.variant);
 
-    // Validate that B % N != 0
-    if B.is_zero() || B.modulo(N).is_zero() {
+    // Safeguard against malicious B values (must lie in [2, N - 2])
+    if let Some(reason) = check_public_ephemeral(B, N) {
       return Err(Error::new(
-        Status::InvalidArg,
-        "Server's public ephemeral value is invalid".to_string(),
+        SrpError::InvalidPublicEphemeral,
+        format!("Server's public ephemeral value {}", reason),
       ));
     }
 
     // u = H(A, B)
-    let u = H(&[&A, &B]);
+    let u = H(self.hash, &[A, B]);
 
-    // s    User's salt
-    let s = SrpInteger::from_hex(&salt).map_err(|e| Error::new(Status::InvalidArg, e))?;
-
-    // x    Private key
-    let x = SrpInteger::from_hex(&private_key).map_err(|e| Error::new(Status::InvalidArg, e))?;
+    // Abort if u == 0
+    if u.is_zero() {
+      return Err(Error::new(
+        SrpError::ZeroScrambler,
+        "Scrambling parameter is zero",
+      ));
+    }
 
     // Compute session key
     // S = (B - k*(g^x))^(a + ux)
-    let S = B.subtract_mult_pow(k, g, &x, &a, &u, N);
-    let K = H(&[&S]);
-
-    // I    Username
-    let I = username;
+    let S = SecretInteger::new(B.subtract_mult_pow(k, g, x, a, &u, N));
+    let K = SecretInteger::new(H(self.hash, &[&S]));
 
     // Get hashed value of identity
-    let I_hash = H_str(&I);
+    let I_hash = H_str(self.hash, I);
 
     // Use XOR of hash(N) and hash(g)
-    let N_g_xor = get_h_N_xor_h_g(self.group);
+    let N_g_xor = self.group.h_N_xor_h_g(self.hash);
 
     // Generate client's proof
     // M1 = H(H(N) XOR H(g), H(I), s, A, B, K)
-    let M1 = H(&[N_g_xor, &I_hash, &s, &A, &B, &K]);
+    let M1 = H(self.hash, &[N_g_xor, &I_hash, s, A, B, &K]);
 
-    Ok(ClientSession {
-      key: K.to_hex(),
-      proof: M1.to_hex(),
-    })
+    Ok((K, M1))
   }
 
-  /// Verify the server's session proof
-  #[napi]
-  pub fn verify_session(
+  // Verify that M2 = H(A, M1, K)
+  pub(crate) fn check_server_proof(
     &self,
-    client_public_ephemeral: String,
-    client_session: ClientSession,
-    server_session_proof: String,
+    A: &SrpInteger,
+    M1: &SrpInteger,
+    K: &SecretInteger,
+    M2_bytes: &[u8],
   ) -> Result<()> {
-    // A    Client's public ephemeral value
-    let A = SrpInteger::from_hex(&client_public_ephemeral)
-      .map_err(|e| Error::new(Status::InvalidArg, e))?;
-
-    // M1    Client's proof
-    let M1 =
-      SrpInteger::from_hex(&client_session.proof).map_err(|e| Error::new(Status::InvalidArg, e))?;
-
-    // K    Session key
-    let K = hex::decode(&client_session.key)
-      .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+    let expected_M2 = H(self.hash, &[A, M1, K]);
 
-    // M2    Server's proof
-    let M2 =
-      SrpInteger::from_hex(&server_session_proof).map_err(|e| Error::new(Status::InvalidArg, e))?;
-
-    // Verify that M2 = H(A, M1, K)
-    let K_srp = SrpInteger::from_bytes(&K);
-    let expected_M2 = H(&[&A, &M1, &K_srp]);
-
-    if !expected_M2.equals(&M2) {
+    if !proof_matches(&expected_M2, M2_bytes, self.hash) {
       return Err(Error::new(
-        Status::GenericFailure,
-        "Server's proof is invalid".to_string(),
+        SrpError::BadServerProof,
+        "Server's proof is invalid",
       ));
     }
 
     Ok(())
   }
-}
 
-// Standalone functions for backward compatibility
-/// Derive the password verifier from the private key
-#[napi]
-pub fn derive_verifier(private_key: String) -> Result<String> {
-  // Default to 2048-bit group for backward compatibility
-  let group = SrpGroup::default();
-  let (N, g, _) = get_group_params(group);
+  // New a and A for a handshake
+  pub(crate) fn handshake(&self, username: String, credentials: Credentials) -> ClientHandshake {
+    let (a, A) = self.ephemeral();
 
-  // x    Private key (derived from password and salt)
-  let x = SrpInteger::from_hex(&private_key).map_err(|e| Error::new(Status::InvalidArg, e))?;
+    ClientHandshake {
+      client: self.clone(),
+      username,
+      A,
+      state: HandshakeState::Started { a, credentials },
+    }
+  }
+}
 
-  // v = g^x (password verifier)
-  let v = g.mod_pow(&x, N);
+// What the client authenticates with
+pub(crate) enum Credentials {
+  Password(Zeroizing<String>, Option<KdfParams>),
+  PrivateKey(SecretInteger),
+}
 
-  Ok(v.to_hex())
+// Secrets a handshake holds at each step
+enum HandshakeState {
+  Started {
+    a: SecretInteger,
+    credentials: Credentials,
+  },
+  Proved {
+    M1: SrpInteger,
+    K: SecretInteger,
+  },
+  Finished,
 }
 
-/// Generate client's ephemeral key pair
-#[napi(js_name = "generateClientEphemeral")]
-pub fn generate_ephemeral() -> ClientEphemeral {
-  // Default to 2048-bit group for backward compatibility
-  let group = SrpGroup::default();
-  let (N, g, _) = get_group_params(group);
+/// Client side of a single handshake, created by Client::start_handshake.
+/// It computes one proof and is finished once; the Node, wasm and C
+/// bindings wrap it.
+pub struct ClientHandshake {
+  pub(crate) client: Client,
+  username: String,
+  pub(crate) A: SrpInteger,
+  state: HandshakeState,
+}
 
-  // a    Secret ephemeral value
-  let a = SrpInteger::random_integer(HASH_OUTPUT_BYTES);
+impl ClientHandshake {
+  /// Client's public ephemeral value A, to send to the server
+  pub fn public(&self) -> Vec<u8> {
+    self.A.to_padded_bytes(self.client.group.prime_bytes())
+  }
 
-  // A = g^a (public ephemeral value)
-  let A = g.mod_pow(&a, N);
+  /// Derive the session from the user's salt and the server's public
+  /// ephemeral value B, and return the client's proof M1 for the server.
+  /// The secret ephemeral is dropped if this fails.
+  pub fn compute_proof(&mut self, salt: &[u8], server_public_ephemeral: &[u8]) -> Result<Vec<u8>> {
+    let hash_bytes = self.client.hash.output_bytes();
+    let M1 = self.prove(
+      &SrpInteger::from_bytes(salt),
+      &SrpInteger::from_bytes(server_public_ephemeral),
+    )?;
+    Ok(M1.to_padded_bytes(hash_bytes))
+  }
 
-  ClientEphemeral {
-    secret: a.to_hex(),
-    public: A.to_hex(),
+  /// Verify the server's proof M2 and return the session key. The session
+  /// is dropped whatever the outcome.
+  pub fn finish(&mut self, server_session_proof: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let K = self.verify(server_session_proof)?;
+    Ok(Zeroizing::new(
+      K.to_padded_bytes(self.client.hash.output_bytes()),
+    ))
   }
-}
 
-/// Derive the session key and proof on the client side
-#[napi(js_name = "deriveClientSession")]
-pub fn derive_session(
-  client_secret_ephemeral: String,
-  server_public_ephemeral: String,
-  salt: String,
-  username: String,
-  private_key: String,
-  client_public_ephemeral: Option<String>,
-) -> Result<ClientSession> {
-  // Create a default client and use its method
-  Client::new(None).derive_session(
-    client_secret_ephemeral,
-    server_public_ephemeral,
-    salt,
-    username,
-    private_key,
-    client_public_ephemeral,
-  )
-}
+  // Fail unless a proof can be computed now
+  pub(crate) fn check_can_prove(&self) -> Result<()> {
+    match self.state {
+      HandshakeState::Started { .. } => Ok(()),
+      _ => Err(Error::new(
+        SrpError::HandshakeOutOfOrder,
+        "Proof has already been computed",
+      )),
+    }
+  }
+
+  // Fail unless the handshake can be finished now
+  pub(crate) fn check_can_finish(&self) -> Result<()> {
+    match self.state {
+      HandshakeState::Proved { .. } => Ok(()),
+      HandshakeState::Finished => Err(Error::new(
+        SrpError::HandshakeFinished,
+        "Handshake has already been finished",
+      )),
+      HandshakeState::Started { .. } => Err(Error::new(
+        SrpError::HandshakeOutOfOrder,
+        "Proof has not been computed yet",
+      )),
+    }
+  }
+
+  // M1 for the user's s and the server's B
+  pub(crate) fn prove(&mut self, s: &SrpInteger, B: &SrpInteger) -> Result<&SrpInteger> {
+    self.check_can_prove()?;
+    let HandshakeState::Started { a, credentials } =
+      std::mem::replace(&mut self.state, HandshakeState::Finished)
+    else {
+      unreachable!("Handshake state was checked above")
+    };
+    let client = &self.client;
+
+    // x    Private key
+    let x = match credentials {
+      Credentials::Password(password, params) => {
+        client.private_key(s, &self.username, &password, params.as_ref())?
+      }
+      Credentials::PrivateKey(x) => x,
+    };
+
+    let (K, M1) = client.session(&a, &self.A, B, s, &self.username, &x)?;
 
-/// Verify the server's session proof
-#[napi]
-pub fn verify_session(
-  client_public_ephemeral: String,
-  client_session: ClientSession,
-  server_session_proof: String,
-) -> Result<()> {
-  // Create a default client and use its method
-  Client::new(None).verify_session(
-    client_public_ephemeral,
-    client_session,
-    server_session_proof,
-  )
+    self.state = HandshakeState::Proved { M1, K };
+    let HandshakeState::Proved { M1, .. } = &self.state else {
+      unreachable!("Handshake state was set above")
+    };
+    Ok(M1)
+  }
+
+  // K once M2 = H(A, M1, K) is checked
+  pub(crate) fn verify(&mut self, M2_bytes: &[u8]) -> Result<SecretInteger> {
+    self.check_can_finish()?;
+    let HandshakeState::Proved { M1, K } =
+      std::mem::replace(&mut self.state, HandshakeState::Finished)
+    else {
+      unreachable!("Handshake state was checked above")
+    };
+
+    self.client.check_server_proof(&self.A, &M1, &K, M2_bytes)?;
+
+    Ok(K)
+  }
 }
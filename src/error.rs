@@ -0,0 +1,128 @@
+#[cfg(feature = "napi")]
+use napi_derive::napi;
+use std::fmt;
+
+/// Stable codes of the errors thrown by this library, set as `err.code`
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[cfg_attr(not(feature = "napi"), derive(Clone, Copy))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum SrpError {
+  /// A value is not valid hex
+  InvalidHex,
+  /// A value is not valid base64
+  InvalidBase64,
+  /// The peer's public ephemeral value is 0, out of range or in a small subgroup
+  InvalidPublicEphemeral,
+  /// The scrambling parameter u = H(A, B) is zero
+  ZeroScrambler,
+  /// The client's proof M1 is wrong, e.g. because of a wrong password
+  BadClientProof,
+  /// The server's proof M2 is wrong
+  BadServerProof,
+  /// No builtin group has the requested size
+  UnknownGroup,
+  /// A custom group's prime or generator is unusable
+  InvalidGroup,
+  /// KDF parameters are malformed, unsupported or out of range
+  InvalidKdfParams,
+  /// A key has the wrong length
+  InvalidKey,
+  /// An option has an invalid value
+  InvalidOption,
+  /// A handshake was used after it had been finished
+  HandshakeFinished,
+  /// A handshake's steps were called out of order
+  HandshakeOutOfOrder,
+  /// A handshake token is malformed, tampered with or sealed under another key
+  InvalidToken,
+  /// A handshake token has expired
+  TokenExpired,
+  /// A handshake token belongs to another group, hash function or verifier
+  TokenMismatch,
+  /// A challenge is unknown, evicted or already used
+  UnknownChallenge,
+  /// A challenge has expired
+  ChallengeExpired,
+  /// A challenge was issued for another verifier
+  ChallengeMismatch,
+}
+
+impl AsRef<str> for SrpError {
+  fn as_ref(&self) -> &str {
+    match self {
+      SrpError::InvalidHex => "InvalidHex",
+      SrpError::InvalidBase64 => "InvalidBase64",
+      SrpError::InvalidPublicEphemeral => "InvalidPublicEphemeral",
+      SrpError::ZeroScrambler => "ZeroScrambler",
+      SrpError::BadClientProof => "BadClientProof",
+      SrpError::BadServerProof => "BadServerProof",
+      SrpError::UnknownGroup => "UnknownGroup",
+      SrpError::InvalidGroup => "InvalidGroup",
+      SrpError::InvalidKdfParams => "InvalidKdfParams",
+      SrpError::InvalidKey => "InvalidKey",
+      SrpError::InvalidOption => "InvalidOption",
+      SrpError::HandshakeFinished => "HandshakeFinished",
+      SrpError::HandshakeOutOfOrder => "HandshakeOutOfOrder",
+      SrpError::InvalidToken => "InvalidToken",
+      SrpError::TokenExpired => "TokenExpired",
+      SrpError::TokenMismatch => "TokenMismatch",
+      SrpError::UnknownChallenge => "UnknownChallenge",
+      SrpError::ChallengeExpired => "ChallengeExpired",
+      SrpError::ChallengeMismatch => "ChallengeMismatch",
+    }
+  }
+}
+
+/// Error returned by the SRP functions: a stable code and a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+  code: SrpError,
+  message: String,
+}
+
+impl Error {
+  pub fn new(code: SrpError, message: impl ToString) -> Self {
+    Error {
+      code,
+      message: message.to_string(),
+    }
+  }
+
+  /// What went wrong, to match on
+  pub fn code(&self) -> SrpError {
+    self.code
+  }
+
+  /// Human-readable description, which may change between versions
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl std::error::Error for Error {}
+
+/// Result of everything that can fail with an SrpError
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(feature = "napi")]
+impl From<Error> for napi::Error<SrpError> {
+  fn from(error: Error) -> Self {
+    napi::Error::new(error.code, error.message)
+  }
+}
+
+#[cfg(feature = "wasm")]
+impl From<Error> for wasm_bindgen::JsValue {
+  fn from(error: Error) -> Self {
+    // Same shape as the Node-API errors: a JS Error with the code as `code`
+    let js_error = js_sys::Error::new(&error.message);
+    let _ = js_sys::Reflect::set(&js_error, &"code".into(), &error.code.as_ref().into());
+    js_error.into()
+  }
+}
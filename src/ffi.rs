@@ -0,0 +1,525 @@
+//! C ABI over the Rust Client and Server. Its conventions for handles,
+//! buffers, status codes and thread safety are described at the top of
+//! `include/srp.h`. build.rs generates the header from this module into
+//! OUT_DIR, and tests::ffi checks the checked-in copy against it.
+use crate::client::{Client, ClientHandshake};
+use crate::error::{Result, SrpError};
+use crate::params::{SrpGroup, SrpHash};
+use crate::server::{Server, ServerHandshake};
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+/// Result of every function taking a handle
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SrpStatus {
+  Ok = 0,
+  InvalidHex = 1,
+  InvalidBase64 = 2,
+  InvalidPublicEphemeral = 3,
+  ZeroScrambler = 4,
+  BadClientProof = 5,
+  BadServerProof = 6,
+  UnknownGroup = 7,
+  InvalidGroup = 8,
+  InvalidKdfParams = 9,
+  InvalidKey = 10,
+  InvalidOption = 11,
+  HandshakeFinished = 12,
+  HandshakeOutOfOrder = 13,
+  InvalidToken = 14,
+  TokenExpired = 15,
+  TokenMismatch = 16,
+  UnknownChallenge = 17,
+  ChallengeExpired = 18,
+  ChallengeMismatch = 19,
+  /// A required pointer is null
+  NullPointer = 100,
+  /// A string is not valid UTF-8
+  InvalidUtf8 = 101,
+  /// The output buffer is too small, `*out_len` holds the required size
+  BufferTooSmall = 102,
+}
+
+impl From<SrpError> for SrpStatus {
+  fn from(code: SrpError) -> Self {
+    match code {
+      SrpError::InvalidHex => SrpStatus::InvalidHex,
+      SrpError::InvalidBase64 => SrpStatus::InvalidBase64,
+      SrpError::InvalidPublicEphemeral => SrpStatus::InvalidPublicEphemeral,
+      SrpError::ZeroScrambler => SrpStatus::ZeroScrambler,
+      SrpError::BadClientProof => SrpStatus::BadClientProof,
+      SrpError::BadServerProof => SrpStatus::BadServerProof,
+      SrpError::UnknownGroup => SrpStatus::UnknownGroup,
+      SrpError::InvalidGroup => SrpStatus::InvalidGroup,
+      SrpError::InvalidKdfParams => SrpStatus::InvalidKdfParams,
+      SrpError::InvalidKey => SrpStatus::InvalidKey,
+      SrpError::InvalidOption => SrpStatus::InvalidOption,
+      SrpError::HandshakeFinished => SrpStatus::HandshakeFinished,
+      SrpError::HandshakeOutOfOrder => SrpStatus::HandshakeOutOfOrder,
+      SrpError::InvalidToken => SrpStatus::InvalidToken,
+      SrpError::TokenExpired => SrpStatus::TokenExpired,
+      SrpError::TokenMismatch => SrpStatus::TokenMismatch,
+      SrpError::UnknownChallenge => SrpStatus::UnknownChallenge,
+      SrpError::ChallengeExpired => SrpStatus::ChallengeExpired,
+      SrpError::ChallengeMismatch => SrpStatus::ChallengeMismatch,
+    }
+  }
+}
+
+type Status<T> = std::result::Result<T, SrpStatus>;
+
+// Run the body of an exported function, turning `Err(status)` into its code
+fn status(body: impl FnOnce() -> Status<()>) -> SrpStatus {
+  match body() {
+    Ok(()) => SrpStatus::Ok,
+    Err(status) => status,
+  }
+}
+
+fn check<T>(result: Result<T>) -> Status<T> {
+  result.map_err(|error| error.code().into())
+}
+
+// Builtin group by size in bits, 0 for the default 2048-bit group
+fn group(bits: u32) -> Status<SrpGroup> {
+  match bits {
+    0 => Ok(SrpGroup::default()),
+    bits => check(SrpGroup::try_from(bits)),
+  }
+}
+
+// Hash function by digest size in bits, 0 for the default SHA-256
+fn hash(bits: u32) -> Status<SrpHash> {
+  match bits {
+    0 | 256 => Ok(SrpHash::SHA256),
+    160 => Ok(SrpHash::SHA1),
+    384 => Ok(SrpHash::SHA384),
+    512 => Ok(SrpHash::SHA512),
+    _ => Err(SrpStatus::InvalidOption),
+  }
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Status<&'a [u8]> {
+  match (data.is_null(), len) {
+    (_, 0) => Ok(&[]),
+    (true, _) => Err(SrpStatus::NullPointer),
+    (false, len) => Ok(std::slice::from_raw_parts(data, len)),
+  }
+}
+
+unsafe fn string<'a>(value: *const c_char) -> Status<&'a str> {
+  if value.is_null() {
+    return Err(SrpStatus::NullPointer);
+  }
+  CStr::from_ptr(value)
+    .to_str()
+    .map_err(|_| SrpStatus::InvalidUtf8)
+}
+
+unsafe fn handle<'a, T>(value: *mut T) -> Status<&'a mut T> {
+  value.as_mut().ok_or(SrpStatus::NullPointer)
+}
+
+// Check that an output buffer takes `len` bytes, and report that length
+unsafe fn reserve(out: *mut u8, out_len: *mut usize, len: usize) -> Status<()> {
+  let capacity = std::mem::replace(handle(out_len)?, len);
+  if capacity < len {
+    return Err(SrpStatus::BufferTooSmall);
+  }
+  if out.is_null() {
+    return Err(SrpStatus::NullPointer);
+  }
+  Ok(())
+}
+
+unsafe fn write(value: &[u8], out: *mut u8, out_len: *mut usize) -> Status<()> {
+  reserve(out, out_len, value.len())?;
+  ptr::copy_nonoverlapping(value.as_ptr(), out, value.len());
+  Ok(())
+}
+
+unsafe fn put<T>(value: T, out: *mut *mut T) -> Status<()> {
+  *handle(out)? = Box::into_raw(Box::new(value));
+  Ok(())
+}
+
+unsafe fn free<T>(value: *mut T) {
+  if !value.is_null() {
+    drop(Box::from_raw(value));
+  }
+}
+
+/// Client-side SRP implementation
+pub struct SrpClient(Client);
+
+/// Server-side SRP implementation
+pub struct SrpServer(Server);
+
+/// Client side of a single handshake
+pub struct SrpClientHandshake(ClientHandshake);
+
+/// Server side of a single handshake
+pub struct SrpServerHandshake {
+  // Length of the proofs and the session key
+  hash_bytes: usize,
+  state: Option<ServerHandshake>,
+}
+
+impl SrpServerHandshake {
+  fn new(server: &Server, handshake: ServerHandshake) -> Self {
+    SrpServerHandshake {
+      hash_bytes: server.hash().output_bytes(),
+      state: Some(handshake),
+    }
+  }
+}
+
+/// Create a client for a builtin group (size in bits, 0 for 2048) and a
+/// hash function (digest size in bits, 0 for SHA-256)
+///
+/// # Safety
+///
+/// `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_new(
+  group_bits: u32,
+  hash_bits: u32,
+  out: *mut *mut SrpClient,
+) -> SrpStatus {
+  status(|| {
+    put(
+      SrpClient(Client::new(group(group_bits)?, hash(hash_bits)?)),
+      out,
+    )
+  })
+}
+
+/// Free a client; null is ignored
+///
+/// # Safety
+///
+/// `client` must be null or come from `srp_client_new`.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_free(client: *mut SrpClient) {
+  free(client)
+}
+
+/// Generate a random salt for password hashing
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_generate_salt(
+  client: *mut SrpClient,
+  out: *mut u8,
+  out_len: *mut usize,
+) -> SrpStatus {
+  status(|| write(&handle(client)?.0.generate_salt(), out, out_len))
+}
+
+/// Derive the private key from user credentials
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_derive_private_key(
+  client: *mut SrpClient,
+  salt: *const u8,
+  salt_len: usize,
+  username: *const c_char,
+  password: *const c_char,
+  out: *mut u8,
+  out_len: *mut usize,
+) -> SrpStatus {
+  status(|| {
+    let private_key = handle(client)?.0.derive_private_key(
+      bytes(salt, salt_len)?,
+      string(username)?,
+      string(password)?,
+    );
+    write(&private_key, out, out_len)
+  })
+}
+
+/// Derive the password verifier from the private key
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_derive_verifier(
+  client: *mut SrpClient,
+  private_key: *const u8,
+  private_key_len: usize,
+  out: *mut u8,
+  out_len: *mut usize,
+) -> SrpStatus {
+  status(|| {
+    let verifier = handle(client)?
+      .0
+      .derive_verifier(bytes(private_key, private_key_len)?);
+    write(&verifier, out, out_len)
+  })
+}
+
+/// Start a handshake from the user's password
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_start_handshake(
+  client: *mut SrpClient,
+  username: *const c_char,
+  password: *const c_char,
+  out: *mut *mut SrpClientHandshake,
+) -> SrpStatus {
+  status(|| {
+    let client = &handle(client)?.0;
+    let handshake = client.start_handshake(string(username)?, string(password)?, None);
+    put(SrpClientHandshake(handshake), out)
+  })
+}
+
+/// Client's public ephemeral value A, to send to the server
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_handshake_public(
+  handshake: *mut SrpClientHandshake,
+  out: *mut u8,
+  out_len: *mut usize,
+) -> SrpStatus {
+  status(|| write(&handle(handshake)?.0.public(), out, out_len))
+}
+
+/// Derive the session from the user's salt and the server's public
+/// ephemeral value B, and write the client's proof M1 for the server
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_handshake_compute_proof(
+  handshake: *mut SrpClientHandshake,
+  salt: *const u8,
+  salt_len: usize,
+  server_public: *const u8,
+  server_public_len: usize,
+  out: *mut u8,
+  out_len: *mut usize,
+) -> SrpStatus {
+  status(|| {
+    let handshake = &mut handle(handshake)?.0;
+    let salt = bytes(salt, salt_len)?;
+    let server_public = bytes(server_public, server_public_len)?;
+    check(handshake.check_can_prove())?;
+    reserve(out, out_len, handshake.client.hash().output_bytes())?;
+
+    let proof = check(handshake.compute_proof(salt, server_public))?;
+    write(&proof, out, out_len)
+  })
+}
+
+/// Verify the server's proof M2 and write the session key. The session is
+/// dropped whatever the outcome, except on null pointers and short buffers.
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_handshake_finish(
+  handshake: *mut SrpClientHandshake,
+  server_proof: *const u8,
+  server_proof_len: usize,
+  key_out: *mut u8,
+  key_out_len: *mut usize,
+) -> SrpStatus {
+  status(|| {
+    let handshake = &mut handle(handshake)?.0;
+    let server_proof = bytes(server_proof, server_proof_len)?;
+    check(handshake.check_can_finish())?;
+    reserve(key_out, key_out_len, handshake.client.hash().output_bytes())?;
+
+    let key = check(handshake.finish(server_proof))?;
+    write(&key, key_out, key_out_len)
+  })
+}
+
+/// Free a client handshake; null is ignored
+///
+/// # Safety
+///
+/// `handshake` must be null or come from `srp_client_start_handshake`.
+#[no_mangle]
+pub unsafe extern "C" fn srp_client_handshake_free(handshake: *mut SrpClientHandshake) {
+  free(handshake)
+}
+
+/// Create a server for a builtin group (size in bits, 0 for 2048) and a
+/// hash function (digest size in bits, 0 for SHA-256)
+///
+/// # Safety
+///
+/// `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn srp_server_new(
+  group_bits: u32,
+  hash_bits: u32,
+  out: *mut *mut SrpServer,
+) -> SrpStatus {
+  status(|| {
+    put(
+      SrpServer(Server::new(group(group_bits)?, hash(hash_bits)?)),
+      out,
+    )
+  })
+}
+
+/// Free a server; null is ignored
+///
+/// # Safety
+///
+/// `server` must be null or come from `srp_server_new`.
+#[no_mangle]
+pub unsafe extern "C" fn srp_server_free(server: *mut SrpServer) {
+  free(server)
+}
+
+/// Start a handshake from the user's stored salt and verifier
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_server_start_handshake(
+  server: *mut SrpServer,
+  username: *const c_char,
+  salt: *const u8,
+  salt_len: usize,
+  verifier: *const u8,
+  verifier_len: usize,
+  out: *mut *mut SrpServerHandshake,
+) -> SrpStatus {
+  status(|| {
+    let server = &handle(server)?.0;
+    let handshake = server.start_handshake(
+      string(username)?,
+      bytes(salt, salt_len)?,
+      bytes(verifier, verifier_len)?,
+    );
+    put(SrpServerHandshake::new(server, handshake), out)
+  })
+}
+
+/// Start a handshake for an unknown user, indistinguishable from a real one
+/// and always failing at the proof. `key` is a server-wide secret of at
+/// least 32 bytes.
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_server_fake_user_challenge(
+  server: *mut SrpServer,
+  username: *const c_char,
+  key: *const u8,
+  key_len: usize,
+  out: *mut *mut SrpServerHandshake,
+) -> SrpStatus {
+  status(|| {
+    let server = &handle(server)?.0;
+    let handshake = check(server.fake_user_challenge(string(username)?, bytes(key, key_len)?))?;
+    put(SrpServerHandshake::new(server, handshake), out)
+  })
+}
+
+/// Server's public ephemeral value B, to send to the client
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_server_handshake_public(
+  handshake: *mut SrpServerHandshake,
+  out: *mut u8,
+  out_len: *mut usize,
+) -> SrpStatus {
+  status(|| match &handle(handshake)?.state {
+    Some(handshake) => write(&handshake.public(), out, out_len),
+    None => Err(SrpStatus::HandshakeFinished),
+  })
+}
+
+/// User's salt, to send to the client along with B
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+pub unsafe extern "C" fn srp_server_handshake_salt(
+  handshake: *mut SrpServerHandshake,
+  out: *mut u8,
+  out_len: *mut usize,
+) -> SrpStatus {
+  status(|| match &handle(handshake)?.state {
+    Some(handshake) => write(&handshake.salt(), out, out_len),
+    None => Err(SrpStatus::HandshakeFinished),
+  })
+}
+
+/// Verify the client's proof M1, then write the session key and the
+/// server's proof M2 for the client. The session is dropped whatever the
+/// outcome, except on null pointers and short buffers.
+///
+/// # Safety
+///
+/// See the conventions at the top of srp.h.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn srp_server_handshake_finish(
+  handshake: *mut SrpServerHandshake,
+  client_public: *const u8,
+  client_public_len: usize,
+  client_proof: *const u8,
+  client_proof_len: usize,
+  key_out: *mut u8,
+  key_out_len: *mut usize,
+  proof_out: *mut u8,
+  proof_out_len: *mut usize,
+) -> SrpStatus {
+  status(|| {
+    let handshake = handle(handshake)?;
+    let client_public = bytes(client_public, client_public_len)?;
+    let client_proof = bytes(client_proof, client_proof_len)?;
+    if handshake.state.is_none() {
+      return Err(SrpStatus::HandshakeFinished);
+    }
+    reserve(key_out, key_out_len, handshake.hash_bytes)?;
+    reserve(proof_out, proof_out_len, handshake.hash_bytes)?;
+
+    let started = handshake
+      .state
+      .take()
+      .expect("Handshake state was checked above");
+    let session = check(started.finish(client_public, client_proof))?;
+    write(&session.key, key_out, key_out_len)?;
+    write(&session.proof, proof_out, proof_out_len)
+  })
+}
+
+/// Free a server handshake; null is ignored
+///
+/// # Safety
+///
+/// `handshake` must be null or come from `srp_server_start_handshake` or
+/// `srp_server_fake_user_challenge`.
+#[no_mangle]
+pub unsafe extern "C" fn srp_server_handshake_free(handshake: *mut SrpServerHandshake) {
+  free(handshake)
+}
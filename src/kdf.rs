@@ -0,0 +1,292 @@
+use crate::error::{Error, Result, SrpError};
+use crate::params::SrpHash;
+use argon2::{Algorithm, Argon2, Version};
+#[cfg(feature = "napi")]
+use napi_derive::napi;
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+use std::fmt;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+// Argon2id defaults (OWASP: 19 MiB, 2 passes, 1 lane)
+const ARGON2_MEMORY_COST: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+
+// scrypt defaults (N = 2^17, r = 8, p = 1)
+const SCRYPT_LOG_N: u32 = 17;
+const SCRYPT_BLOCK_SIZE: u32 = 8;
+const SCRYPT_PARALLELISM: u32 = 1;
+
+// PBKDF2 default iteration count
+const PBKDF2_ITERATIONS: u32 = 600_000;
+
+// Upper bounds on every cost. The parameters come from the server, so a
+// malicious or broken one must not make the client allocate terabytes.
+const ARGON2_MAX_MEMORY_COST: u64 = 1024 * 1024; // 1 GiB in KiB
+const ARGON2_MAX_ITERATIONS: u64 = 64;
+const ARGON2_MAX_PARALLELISM: u64 = 64;
+const SCRYPT_MAX_LOG_N: u64 = 20;
+const SCRYPT_MAX_R_P: u64 = 64;
+const SCRYPT_MAX_MEMORY: u64 = 1 << 30; // 128 * r * N bytes
+const PBKDF2_MAX_ITERATIONS: u64 = 10_000_000;
+
+/// Password hashing function used to stretch the credentials
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(not(feature = "napi"), derive(Clone, Copy))]
+#[derive(Debug, PartialEq)]
+pub enum KdfAlgorithm {
+  /// Argon2id (RFC 9106)
+  Argon2id,
+  /// scrypt (RFC 7914)
+  Scrypt,
+  /// PBKDF2 with HMAC over the instance's hash function (RFC 8018)
+  Pbkdf2,
+}
+
+/// Cost parameters for the private key derivation.
+/// Fields that do not apply to the algorithm are ignored, missing ones use defaults.
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KdfParams {
+  pub algorithm: KdfAlgorithm,
+  /// Argon2id memory cost in KiB
+  pub memory_cost: Option<u32>,
+  /// Argon2id passes or PBKDF2 iterations
+  pub iterations: Option<u32>,
+  /// Argon2id lanes or scrypt p
+  pub parallelism: Option<u32>,
+  /// scrypt log2(N)
+  pub log_n: Option<u32>,
+  /// scrypt r
+  pub block_size: Option<u32>,
+}
+
+// KdfParams with every default filled in
+#[derive(Debug, PartialEq)]
+enum Kdf {
+  Argon2id { m: u32, t: u32, p: u32 },
+  Scrypt { ln: u32, r: u32, p: u32 },
+  Pbkdf2 { i: u32 },
+}
+
+impl From<&KdfParams> for Kdf {
+  fn from(params: &KdfParams) -> Self {
+    match params.algorithm {
+      KdfAlgorithm::Argon2id => Kdf::Argon2id {
+        m: params.memory_cost.unwrap_or(ARGON2_MEMORY_COST),
+        t: params.iterations.unwrap_or(ARGON2_ITERATIONS),
+        p: params.parallelism.unwrap_or(ARGON2_PARALLELISM),
+      },
+      KdfAlgorithm::Scrypt => Kdf::Scrypt {
+        ln: params.log_n.unwrap_or(SCRYPT_LOG_N),
+        r: params.block_size.unwrap_or(SCRYPT_BLOCK_SIZE),
+        p: params.parallelism.unwrap_or(SCRYPT_PARALLELISM),
+      },
+      KdfAlgorithm::Pbkdf2 => Kdf::Pbkdf2 {
+        i: params.iterations.unwrap_or(PBKDF2_ITERATIONS),
+      },
+    }
+  }
+}
+
+impl From<Kdf> for KdfParams {
+  fn from(kdf: Kdf) -> Self {
+    let (algorithm, memory_cost, iterations, parallelism, log_n, block_size) = match kdf {
+      Kdf::Argon2id { m, t, p } => (
+        KdfAlgorithm::Argon2id,
+        Some(m),
+        Some(t),
+        Some(p),
+        None,
+        None,
+      ),
+      Kdf::Scrypt { ln, r, p } => (KdfAlgorithm::Scrypt, None, None, Some(p), Some(ln), Some(r)),
+      Kdf::Pbkdf2 { i } => (KdfAlgorithm::Pbkdf2, None, Some(i), None, None, None),
+    };
+    KdfParams {
+      algorithm,
+      memory_cost,
+      iterations,
+      parallelism,
+      log_n,
+      block_size,
+    }
+  }
+}
+
+// PHC-style encoding without the salt, which SRP already transports:
+//   $argon2id$v=19$m=19456,t=2,p=1
+//   $scrypt$ln=17,r=8,p=1
+//   $pbkdf2$i=600000
+impl fmt::Display for Kdf {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Kdf::Argon2id { m, t, p } => write!(f, "$argon2id$v=19$m={},t={},p={}", m, t, p),
+      Kdf::Scrypt { ln, r, p } => write!(f, "$scrypt$ln={},r={},p={}", ln, r, p),
+      Kdf::Pbkdf2 { i } => write!(f, "$pbkdf2$i={}", i),
+    }
+  }
+}
+
+impl FromStr for Kdf {
+  type Err = String;
+
+  fn from_str(encoded: &str) -> std::result::Result<Self, Self::Err> {
+    let fields: Vec<&str> = encoded.split('$').collect();
+    let (id, version, params) = match fields.as_slice() {
+      ["", id, params] => (*id, None, *params),
+      ["", id, version, params] => (*id, Some(*version), *params),
+      _ => return Err(format!("Malformed KDF parameters: {}", encoded)),
+    };
+
+    // Parse "key=value,key=value" into the expected keys, in order
+    let values = |keys: &[&str]| -> std::result::Result<Vec<u32>, String> {
+      let pairs: Vec<&str> = params.split(',').collect();
+      if pairs.len() != keys.len() {
+        return Err(format!("Malformed KDF parameters: {}", encoded));
+      }
+      keys
+        .iter()
+        .zip(pairs)
+        .map(|(key, pair)| match pair.split_once('=') {
+          Some((k, v)) if k == *key => v
+            .parse()
+            .map_err(|_| format!("Invalid KDF parameter {}: {}", key, v)),
+          _ => Err(format!("Expected KDF parameter {} in: {}", key, encoded)),
+        })
+        .collect()
+    };
+
+    let kdf = match (id, version) {
+      ("argon2id", Some("v=19")) => {
+        let v = values(&["m", "t", "p"])?;
+        Kdf::Argon2id {
+          m: v[0],
+          t: v[1],
+          p: v[2],
+        }
+      }
+      ("scrypt", None) => {
+        let v = values(&["ln", "r", "p"])?;
+        Kdf::Scrypt {
+          ln: v[0],
+          r: v[1],
+          p: v[2],
+        }
+      }
+      ("pbkdf2", None) => {
+        let v = values(&["i"])?;
+        Kdf::Pbkdf2 { i: v[0] }
+      }
+      _ => return Err(format!("Unsupported KDF: {}", encoded)),
+    };
+    kdf.check_bounds()?;
+    Ok(kdf)
+  }
+}
+
+// Fail if `value` is above `max`
+fn at_most(name: &str, value: u64, max: u64) -> std::result::Result<(), String> {
+  if value > max {
+    return Err(format!("{} is too large: {}, at most {}", name, value, max));
+  }
+  Ok(())
+}
+
+impl Kdf {
+  // Reject costs above the maximums before anything is allocated
+  fn check_bounds(&self) -> std::result::Result<(), String> {
+    match *self {
+      Kdf::Argon2id { m, t, p } => {
+        at_most("Argon2id memory cost", m.into(), ARGON2_MAX_MEMORY_COST)?;
+        at_most("Argon2id iterations", t.into(), ARGON2_MAX_ITERATIONS)?;
+        at_most("Argon2id parallelism", p.into(), ARGON2_MAX_PARALLELISM)
+      }
+      Kdf::Scrypt { ln, r, p } => {
+        at_most("scrypt ln", ln.into(), SCRYPT_MAX_LOG_N)?;
+        at_most("scrypt r * p", u64::from(r) * u64::from(p), SCRYPT_MAX_R_P)?;
+        at_most(
+          "scrypt memory",
+          (128 * u64::from(r)) << ln,
+          SCRYPT_MAX_MEMORY,
+        )
+      }
+      Kdf::Pbkdf2 { i } => at_most("PBKDF2 iterations", i.into(), PBKDF2_MAX_ITERATIONS),
+    }
+  }
+
+  // Stretch the credentials into one hash output worth of key material
+  fn derive(
+    &self,
+    hash: SrpHash,
+    password: &[u8],
+    salt: &[u8],
+  ) -> std::result::Result<Zeroizing<Vec<u8>>, String> {
+    self.check_bounds()?;
+    let mut out = Zeroizing::new(vec![0u8; hash.output_bytes()]);
+
+    match *self {
+      Kdf::Argon2id { m, t, p } => {
+        let params = argon2::Params::new(m, t, p, Some(out.len())).map_err(|e| e.to_string())?;
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+          .hash_password_into(password, salt, &mut out)
+          .map_err(|e| e.to_string())?;
+      }
+      Kdf::Scrypt { ln, r, p } => {
+        let ln = u8::try_from(ln).map_err(|_| format!("scrypt ln is too large: {}", ln))?;
+        let params = scrypt::Params::new(ln, r, p, out.len()).map_err(|e| e.to_string())?;
+        scrypt::scrypt(password, salt, &params, &mut out).map_err(|e| e.to_string())?;
+      }
+      Kdf::Pbkdf2 { i } => {
+        if i == 0 {
+          return Err("PBKDF2 needs at least one iteration".to_string());
+        }
+        match hash {
+          SrpHash::SHA1 => pbkdf2::pbkdf2_hmac::<Sha1>(password, salt, i, &mut out),
+          SrpHash::SHA256 => pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, i, &mut out),
+          SrpHash::SHA384 => pbkdf2::pbkdf2_hmac::<Sha384>(password, salt, i, &mut out),
+          SrpHash::SHA512 => pbkdf2::pbkdf2_hmac::<Sha512>(password, salt, i, &mut out),
+        }
+      }
+    }
+
+    Ok(out)
+  }
+}
+
+/// Encoding of the parameters with every default filled in, for the
+/// server to store next to the salt
+impl fmt::Display for KdfParams {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    Kdf::from(self).fmt(f)
+  }
+}
+
+/// Parameters from their encoded form
+impl FromStr for KdfParams {
+  type Err = Error;
+
+  fn from_str(encoded: &str) -> Result<Self> {
+    encoded
+      .parse()
+      .map(|kdf: Kdf| kdf.into())
+      .map_err(|e: String| Error::new(SrpError::InvalidKdfParams, e))
+  }
+}
+
+/// Stretch `I:p` with the given KDF, salted with the SRP salt
+pub fn stretch(
+  hash: SrpHash,
+  params: &KdfParams,
+  password: &[u8],
+  salt: &[u8],
+) -> Result<Zeroizing<Vec<u8>>> {
+  Kdf::from(params).derive(hash, password, salt).map_err(|e| {
+    Error::new(
+      SrpError::InvalidKdfParams,
+      format!("Invalid KDF parameters: {}", e),
+    )
+  })
+}
@@ -1,20 +1,33 @@
 #![deny(clippy::all)]
+// Variable names follow the SRP-6a notation (N, g, A, B, M1, ...)
+#![allow(non_snake_case)]
 
 mod client;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod kdf;
+#[cfg(feature = "napi")]
+pub mod node;
 mod params;
+mod rng;
 mod server;
 mod srp_integer;
+#[cfg(test)]
+mod tests;
+mod token;
+mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-// Re-export all public types and functions
-pub use client::{Client, ClientEphemeral, ClientSession};
-pub use params::{srp_group_from_value, SrpGroup};
-pub use server::{Server, ServerEphemeral, ServerSession};
-
-// Re-export standalone functions for backward compatibility
-pub use client::{
-  derive_private_key, derive_session as derive_client_session, derive_verifier,
-  generate_ephemeral as generate_client_ephemeral, generate_salt, verify_session,
-};
+// Rust API; the Node-API bindings in `node`, the wasm-bindgen ones in `wasm`
+// and the C ABI in `ffi` wrap the same types
+pub use client::{Client, ClientEphemeral, ClientHandshake, ClientSession};
+pub use error::{Error, Result, SrpError};
+pub use kdf::{KdfAlgorithm, KdfParams};
+pub use params::{Group, SrpGroup, SrpHash, SrpVariant};
+pub use rng::SrpRng;
 pub use server::{
-  derive_session as derive_server_session, generate_ephemeral as generate_server_ephemeral,
+  ChallengeStore, Server, ServerChallenge, ServerEphemeral, ServerHandshake, ServerSession,
 };
+pub use validate::{validate_group, GroupReport};
@@ -0,0 +1,448 @@
+use crate::client::{Client as SrpClient, ClientHandshake as Handshake, Credentials};
+use crate::kdf::KdfParams;
+use crate::node::encoding::{into_hex, take, SrpEncoding};
+use crate::node::kdf::resolve;
+use crate::node::params::{CustomGroup, SrpOptions};
+use crate::node::{reject, Result};
+use crate::params::{SrpGroup, SECRET_BYTES};
+use crate::rng::RngSource;
+use crate::srp_integer::{SecretInteger, SrpInteger};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use zeroize::Zeroizing;
+
+/// Generate a random salt for password hashing
+#[napi]
+pub fn generate_salt() -> String {
+  // s    User's salt
+  let s = SrpInteger::random_integer(&RngSource::os(), SECRET_BYTES);
+  s.to_hex()
+}
+
+/// Derive the private key from user credentials
+#[napi]
+pub fn derive_private_key(salt: String, username: String, password: String) -> Result<String> {
+  // Create a default client and use its method
+  Client::new(None, None)
+    .derive_private_key(Either::A(salt), username, password)
+    .map(into_hex)
+}
+
+/// Client's ephemeral key pair
+#[napi]
+pub struct ClientEphemeral {
+  pub secret: Either<String, Buffer>,
+  pub public: Either<String, Buffer>,
+}
+
+/// Client's session key and proof
+#[napi(object)]
+pub struct ClientSession {
+  pub key: Either<String, Buffer>,
+  pub proof: Either<String, Buffer>,
+}
+
+/// Client-side SRP implementation
+#[napi]
+#[derive(Clone)]
+pub struct Client {
+  inner: SrpClient,
+  encoding: SrpEncoding,
+}
+
+#[napi]
+impl Client {
+  /// Create a new Client instance with optional parameter group (builtin or custom) and options
+  #[napi(constructor)]
+  pub fn new(
+    group: Option<Either<SrpGroup, ClassInstance<CustomGroup>>>,
+    options: Option<SrpOptions>,
+  ) -> Self {
+    let options = options.unwrap_or_default();
+    Client {
+      inner: SrpClient::new(group, options.hash.unwrap_or_default()),
+      encoding: options.encoding.unwrap_or_default(),
+    }
+  }
+
+  /// Generate a random salt for password hashing
+  #[napi]
+  pub fn generate_salt(&self) -> Either<String, Buffer> {
+    self.encoding.encode(&self.inner.salt(), SECRET_BYTES)
+  }
+
+  /// Derive the private key from user credentials
+  #[napi]
+  pub fn derive_private_key(
+    &self,
+    salt: Either<String, Buffer>,
+    username: String,
+    password: String,
+  ) -> Result<Either<String, Buffer>> {
+    // s    User's salt
+    let s = self.encoding.integer(&salt)?;
+    let password = Zeroizing::new(password);
+
+    let x = self.inner.private_key(&s, &username, &password, None)?;
+
+    Ok(self.encoding.encode(&x, self.inner.hash.output_bytes()))
+  }
+
+  /// Derive the private key with a password hashing function, given as
+  /// KdfParams or in the form produced by encodeKdfParams
+  #[napi]
+  pub fn derive_private_key_with(
+    &self,
+    salt: Either<String, Buffer>,
+    username: String,
+    password: String,
+    params: Either<String, KdfParams>,
+  ) -> Result<Either<String, Buffer>> {
+    // s    User's salt
+    let s = self.encoding.integer(&salt)?;
+    let password = Zeroizing::new(password);
+    let params = resolve(params)?;
+
+    let x = self
+      .inner
+      .private_key(&s, &username, &password, Some(&params))?;
+
+    Ok(self.encoding.encode(&x, self.inner.hash.output_bytes()))
+  }
+
+  /// Derive the password verifier from the private key
+  #[napi]
+  pub fn derive_verifier(
+    &self,
+    private_key: Either<String, Buffer>,
+  ) -> Result<Either<String, Buffer>> {
+    // x    Private key (derived from password and salt)
+    let x = self.encoding.secret(private_key)?;
+
+    let v = self.inner.verifier(&x);
+
+    Ok(self.encoding.encode(&v, self.inner.group.prime_bytes()))
+  }
+
+  /// Derive the password verifier on the libuv thread pool
+  #[napi(ts_return_type = "Promise<string | Buffer>")]
+  pub fn derive_verifier_async(
+    &self,
+    private_key: Either<String, Buffer>,
+  ) -> AsyncTask<DeriveVerifierTask> {
+    AsyncTask::new(DeriveVerifierTask {
+      client: self.clone(),
+      private_key,
+    })
+  }
+
+  /// Generate client's ephemeral key pair
+  #[napi]
+  pub fn generate_ephemeral(&self) -> ClientEphemeral {
+    let (a, A) = self.inner.ephemeral();
+
+    ClientEphemeral {
+      secret: self.encoding.encode(&a, SECRET_BYTES),
+      public: self.encoding.encode(&A, self.inner.group.prime_bytes()),
+    }
+  }
+
+  /// Derive the session key and proof on the client side
+  #[napi]
+  pub fn derive_session(
+    &self,
+    client_secret_ephemeral: Either<String, Buffer>,
+    server_public_ephemeral: Either<String, Buffer>,
+    salt: Either<String, Buffer>,
+    username: String,
+    private_key: Either<String, Buffer>,
+    client_public_ephemeral: Option<Either<String, Buffer>>,
+  ) -> Result<ClientSession> {
+    // N    A large safe prime
+    // g    A generator modulo N
+    let (N, g) = self.inner.group.constants();
+
+    // a    Secret ephemeral value
+    let a = self.encoding.secret(client_secret_ephemeral)?;
+
+    // A    Public ephemeral value
+    let A = match client_public_ephemeral {
+      Some(A_in) => self.encoding.integer(&A_in)?,
+      None => g.mod_pow(&a, N),
+    };
+
+    // B    Server's public ephemeral value
+    let B = self.encoding.integer(&server_public_ephemeral)?;
+
+    // s    User's salt
+    let s = self.encoding.integer(&salt)?;
+
+    // x    Private key
+    let x = self.encoding.secret(private_key)?;
+
+    let (K, M1) = self.inner.session(&a, &A, &B, &s, &username, &x)?;
+
+    Ok(ClientSession {
+      key: self.encoding.encode(&K, self.inner.hash.output_bytes()),
+      proof: self.encoding.encode(&M1, self.inner.hash.output_bytes()),
+    })
+  }
+
+  /// Derive the session key and proof on the libuv thread pool
+  #[napi(ts_return_type = "Promise<ClientSession>")]
+  pub fn derive_session_async(
+    &self,
+    client_secret_ephemeral: Either<String, Buffer>,
+    server_public_ephemeral: Either<String, Buffer>,
+    salt: Either<String, Buffer>,
+    username: String,
+    private_key: Either<String, Buffer>,
+    client_public_ephemeral: Option<Either<String, Buffer>>,
+  ) -> AsyncTask<DeriveClientSessionTask> {
+    AsyncTask::new(DeriveClientSessionTask {
+      client: self.clone(),
+      client_secret_ephemeral,
+      server_public_ephemeral,
+      salt,
+      username,
+      private_key,
+      client_public_ephemeral,
+    })
+  }
+
+  /// Verify the server's session proof
+  #[napi]
+  pub fn verify_session(
+    &self,
+    client_public_ephemeral: Either<String, Buffer>,
+    client_session: ClientSession,
+    server_session_proof: Either<String, Buffer>,
+  ) -> Result<()> {
+    // A    Client's public ephemeral value
+    let A = self.encoding.integer(&client_public_ephemeral)?;
+
+    // M1    Client's proof
+    let M1 = self.encoding.integer(&client_session.proof)?;
+
+    // K    Session key
+    let K_bytes = Zeroizing::new(self.encoding.bytes(&client_session.key)?);
+    let K = SecretInteger::new(SrpInteger::from_bytes(&K_bytes));
+
+    // M2    Server's proof
+    let M2_bytes = self.encoding.bytes(&server_session_proof)?;
+
+    Ok(self.inner.check_server_proof(&A, &M1, &K, &M2_bytes)?)
+  }
+
+  /// Start a handshake from the user's password, optionally stretched with
+  /// KdfParams as in derivePrivateKeyWith. It keeps the secret ephemeral and
+  /// the session to itself.
+  #[napi]
+  pub fn start_handshake(
+    &self,
+    username: String,
+    password: String,
+    params: Option<Either<String, KdfParams>>,
+  ) -> Result<ClientHandshake> {
+    let params = params.map(resolve).transpose()?;
+    Ok(self.handshake(
+      username,
+      Credentials::Password(Zeroizing::new(password), params),
+    ))
+  }
+
+  /// Start a handshake from a private key derived at registration
+  #[napi]
+  pub fn start_handshake_with_private_key(
+    &self,
+    username: String,
+    private_key: Either<String, Buffer>,
+  ) -> Result<ClientHandshake> {
+    let x = self.encoding.secret(private_key)?;
+    Ok(self.handshake(username, Credentials::PrivateKey(x)))
+  }
+}
+
+// Only built with the test-rng feature, so release builds never ship it
+#[cfg(feature = "test-rng")]
+#[napi]
+impl Client {
+  /// Create a Client whose salts and ephemerals come from a seeded DRBG.
+  /// For reproducible tests only, never use this in production.
+  #[napi(factory)]
+  pub fn with_test_rng(
+    seed: u32,
+    group: Option<Either<SrpGroup, ClassInstance<CustomGroup>>>,
+    options: Option<SrpOptions>,
+  ) -> Self {
+    let mut client = Client::new(group, options);
+    client.inner.rng = RngSource::seeded(seed);
+    client
+  }
+}
+
+impl Client {
+  fn handshake(&self, username: String, credentials: Credentials) -> ClientHandshake {
+    ClientHandshake {
+      encoding: self.encoding,
+      inner: self.inner.handshake(username, credentials),
+    }
+  }
+}
+
+/// Client side of a single handshake, created by Client.startHandshake
+#[napi]
+pub struct ClientHandshake {
+  encoding: SrpEncoding,
+  inner: Handshake,
+}
+
+#[napi]
+impl ClientHandshake {
+  /// Client's public ephemeral value A, to send to the server
+  #[napi(getter)]
+  pub fn public(&self) -> Either<String, Buffer> {
+    let prime_bytes = self.inner.client.group.prime_bytes();
+    self.encoding.encode(&self.inner.A, prime_bytes)
+  }
+
+  /// Derive the session from the user's salt and the server's public
+  /// ephemeral value B, and return the client's proof M1 for the server
+  #[napi]
+  pub fn compute_proof(
+    &mut self,
+    salt: Either<String, Buffer>,
+    server_public_ephemeral: Either<String, Buffer>,
+  ) -> Result<Either<String, Buffer>> {
+    // s    User's salt
+    let s = self.encoding.integer(&salt)?;
+
+    // B    Server's public ephemeral value
+    let B = self.encoding.integer(&server_public_ephemeral)?;
+
+    let hash_bytes = self.inner.client.hash.output_bytes();
+    Ok(self.encoding.encode(self.inner.prove(&s, &B)?, hash_bytes))
+  }
+
+  /// Verify the server's proof M2 and return the session key.
+  /// The session is dropped whatever the outcome.
+  #[napi]
+  pub fn finish(
+    &mut self,
+    server_session_proof: Either<String, Buffer>,
+  ) -> Result<Either<String, Buffer>> {
+    // M2    Server's proof
+    let M2_bytes = self.encoding.bytes(&server_session_proof)?;
+
+    let K = self.inner.verify(&M2_bytes)?;
+
+    Ok(
+      self
+        .encoding
+        .encode(&K, self.inner.client.hash.output_bytes()),
+    )
+  }
+}
+
+/// Client::derive_verifier run off the event loop
+pub struct DeriveVerifierTask {
+  client: Client,
+  private_key: Either<String, Buffer>,
+}
+
+impl Task for DeriveVerifierTask {
+  type Output = Result<Either<String, Buffer>>;
+  type JsValue = Either<String, Buffer>;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    Ok(self.client.derive_verifier(take(&mut self.private_key)))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    output.map_err(|error| reject(env, error))
+  }
+}
+
+/// Client::derive_session run off the event loop
+pub struct DeriveClientSessionTask {
+  client: Client,
+  client_secret_ephemeral: Either<String, Buffer>,
+  server_public_ephemeral: Either<String, Buffer>,
+  salt: Either<String, Buffer>,
+  username: String,
+  private_key: Either<String, Buffer>,
+  client_public_ephemeral: Option<Either<String, Buffer>>,
+}
+
+impl Task for DeriveClientSessionTask {
+  type Output = Result<ClientSession>;
+  type JsValue = ClientSession;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    Ok(self.client.derive_session(
+      take(&mut self.client_secret_ephemeral),
+      take(&mut self.server_public_ephemeral),
+      take(&mut self.salt),
+      std::mem::take(&mut self.username),
+      take(&mut self.private_key),
+      self.client_public_ephemeral.take(),
+    ))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    output.map_err(|error| reject(env, error))
+  }
+}
+
+// Standalone functions for backward compatibility
+/// Derive the password verifier from the private key
+#[napi]
+pub fn derive_verifier(private_key: String) -> Result<String> {
+  // Create a default client and use its method
+  Client::new(None, None)
+    .derive_verifier(Either::A(private_key))
+    .map(into_hex)
+}
+
+/// Generate client's ephemeral key pair
+#[napi(js_name = "generateClientEphemeral")]
+pub fn generate_ephemeral() -> ClientEphemeral {
+  // Create a default client and use its method
+  Client::new(None, None).generate_ephemeral()
+}
+
+/// Derive the session key and proof on the client side
+#[napi(js_name = "deriveClientSession")]
+pub fn derive_session(
+  client_secret_ephemeral: String,
+  server_public_ephemeral: String,
+  salt: String,
+  username: String,
+  private_key: String,
+  client_public_ephemeral: Option<String>,
+) -> Result<ClientSession> {
+  // Create a default client and use its method
+  Client::new(None, None).derive_session(
+    Either::A(client_secret_ephemeral),
+    Either::A(server_public_ephemeral),
+    Either::A(salt),
+    username,
+    Either::A(private_key),
+    client_public_ephemeral.map(Either::A),
+  )
+}
+
+/// Verify the server's session proof
+#[napi]
+pub fn verify_session(
+  client_public_ephemeral: String,
+  client_session: ClientSession,
+  server_session_proof: String,
+) -> Result<()> {
+  // Create a default client and use its method
+  Client::new(None, None).verify_session(
+    Either::A(client_public_ephemeral),
+    client_session,
+    Either::A(server_session_proof),
+  )
+}
@@ -0,0 +1,100 @@
+use crate::error::{Error, Result, SrpError};
+use crate::srp_integer::{SecretInteger, SrpInteger};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use zeroize::Zeroizing;
+
+/// Encoding of salts, keys, ephemerals and proofs exchanged with JS
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, Default, PartialEq)]
+pub enum SrpEncoding {
+  /// Hex strings (default)
+  #[default]
+  Hex,
+  /// Fixed-length, left-padded big-endian Buffers
+  Buffer,
+  /// Base64 strings of the same fixed-length bytes
+  Base64,
+}
+
+impl SrpEncoding {
+  // Raw bytes of a value received from JS. Buffers are used as-is, strings
+  // are base64 in base64 mode and hex otherwise.
+  fn decode(self, value: &str) -> Result<Vec<u8>> {
+    match self {
+      SrpEncoding::Base64 => STANDARD
+        .decode(value.trim())
+        .map_err(|e| Error::new(SrpError::InvalidBase64, e.to_string())),
+      _ => hex::decode(value.trim()).map_err(|e| Error::new(SrpError::InvalidHex, e.to_string())),
+    }
+  }
+
+  /// Byte string received from JS, e.g. a session proof
+  pub fn bytes(self, value: &Either<String, Buffer>) -> Result<Vec<u8>> {
+    match value {
+      Either::A(string) => self.decode(string),
+      Either::B(buffer) => Ok(buffer.to_vec()),
+    }
+  }
+
+  /// Integer received from JS
+  pub fn integer(self, value: &Either<String, Buffer>) -> Result<SrpInteger> {
+    match (self, value) {
+      // Hex keeps going through from_hex, which also accepts odd lengths
+      (SrpEncoding::Hex | SrpEncoding::Buffer, Either::A(hex)) => {
+        SrpInteger::from_hex(hex).map_err(|e| Error::new(SrpError::InvalidHex, e))
+      }
+      _ => Ok(SrpInteger::from_bytes(&self.bytes(value)?)),
+    }
+  }
+
+  /// Secret integer received from JS; the Rust copy of a string is wiped
+  pub fn secret(self, value: Either<String, Buffer>) -> Result<SecretInteger> {
+    match value {
+      Either::A(string) => {
+        let string = Zeroizing::new(string);
+        match self {
+          SrpEncoding::Base64 => {
+            let bytes = Zeroizing::new(self.decode(&string)?);
+            Ok(SecretInteger::new(SrpInteger::from_bytes(&bytes)))
+          }
+          _ => SecretInteger::from_hex(&string).map_err(|e| Error::new(SrpError::InvalidHex, e)),
+        }
+      }
+      Either::B(buffer) => Ok(SecretInteger::new(SrpInteger::from_bytes(&buffer))),
+    }
+  }
+
+  /// Integer returned to JS. Buffers and base64 strings are left-padded to
+  /// `len` bytes, hex strings keep their historical format.
+  pub fn encode(self, value: &SrpInteger, len: usize) -> Either<String, Buffer> {
+    if self == SrpEncoding::Hex {
+      return Either::A(value.to_hex());
+    }
+
+    let padded = value.to_padded_bytes(len);
+
+    match self {
+      SrpEncoding::Base64 => {
+        let padded = Zeroizing::new(padded);
+        Either::A(STANDARD.encode(&*padded))
+      }
+      _ => Either::B(padded.into()),
+    }
+  }
+}
+
+/// Move a value out of an async task, leaving an empty string behind
+pub fn take(value: &mut Either<String, Buffer>) -> Either<String, Buffer> {
+  std::mem::replace(value, Either::A(String::new()))
+}
+
+/// Hex string of a value encoded in hex mode, for the standalone functions
+pub fn into_hex(value: Either<String, Buffer>) -> String {
+  match value {
+    Either::A(hex) => hex,
+    Either::B(buffer) => hex::encode(&buffer),
+  }
+}
@@ -0,0 +1,24 @@
+use crate::kdf::KdfParams;
+use crate::node::Result;
+use napi::bindgen_prelude::Either;
+use napi_derive::napi;
+
+/// KDF parameters given as an object or in their encoded form
+pub fn resolve(params: Either<String, KdfParams>) -> Result<KdfParams> {
+  match params {
+    Either::A(encoded) => Ok(encoded.parse()?),
+    Either::B(params) => Ok(params),
+  }
+}
+
+/// Encode KDF parameters so the server can store them next to the salt
+#[napi]
+pub fn encode_kdf_params(params: KdfParams) -> String {
+  params.to_string()
+}
+
+/// Decode KDF parameters produced by encodeKdfParams
+#[napi]
+pub fn decode_kdf_params(encoded: String) -> Result<KdfParams> {
+  Ok(encoded.parse()?)
+}
@@ -0,0 +1,42 @@
+// Node-API bindings: JS classes wrapping the Rust Client and Server, with
+// values in the SrpEncoding of each instance
+use crate::error::SrpError;
+use napi::bindgen_prelude::{Env, JsError};
+
+mod client;
+mod encoding;
+mod kdf;
+mod params;
+mod server;
+mod validate;
+
+// Re-export all binding types and functions
+pub use client::{Client, ClientEphemeral, ClientHandshake, ClientSession};
+pub use encoding::SrpEncoding;
+pub use kdf::{decode_kdf_params, encode_kdf_params};
+pub use params::{
+  group_parameters, srp_group_from_value, CustomGroup, GroupParameters, SrpOptions,
+};
+pub use server::{
+  ChallengeStore, ChallengeStoreOptions, Server, ServerChallenge, ServerEphemeral, ServerHandshake,
+  ServerSession,
+};
+pub use validate::{validate_group, ValidateGroupOptions};
+
+// Standalone functions, under their JS names
+pub use client::{
+  derive_private_key, derive_session as derive_client_session, derive_verifier,
+  generate_ephemeral as generate_client_ephemeral, generate_salt, verify_session,
+};
+pub use server::{
+  derive_session as derive_server_session, generate_ephemeral as generate_server_ephemeral,
+};
+
+/// Result of every binding, thrown with its SrpError as `err.code`
+pub type Result<T> = napi::Result<T, SrpError>;
+
+/// Async tasks can only reject with a plain napi Error, so build the JS
+/// error here and hand it over as is, code included
+pub fn reject(env: Env, error: napi::Error<SrpError>) -> napi::Error {
+  napi::Error::from(JsError::from(error).into_unknown(env))
+}
@@ -0,0 +1,84 @@
+use crate::error::{Error, SrpError};
+use crate::node::encoding::SrpEncoding;
+use crate::node::Result;
+use crate::params::{Group, SrpGroup, SrpHash};
+use crate::srp_integer::SrpInteger;
+use napi::bindgen_prelude::{ClassInstance, Either};
+use napi_derive::napi;
+
+/// SRP group with a caller-supplied prime N and generator g
+#[napi]
+pub struct CustomGroup {
+  group: Group,
+}
+
+#[napi]
+impl CustomGroup {
+  /// Create a group from a hex-encoded prime N and generator g.
+  /// k and H(N) XOR H(g) are computed once here for every hash function.
+  #[napi(constructor)]
+  pub fn new(prime: String, generator: String) -> Result<Self> {
+    let N_ = SrpInteger::from_hex(&prime).map_err(|e| Error::new(SrpError::InvalidHex, e))?;
+    let g_ = SrpInteger::from_hex(&generator).map_err(|e| Error::new(SrpError::InvalidHex, e))?;
+
+    Ok(CustomGroup {
+      group: Group::custom_from(N_, g_)?,
+    })
+  }
+
+  /// Hex-encoded prime N
+  #[napi(getter)]
+  pub fn prime(&self) -> String {
+    self.group.constants().0.to_hex()
+  }
+
+  /// Hex-encoded generator g
+  #[napi(getter)]
+  pub fn generator(&self) -> String {
+    self.group.constants().1.to_hex()
+  }
+}
+
+impl From<Option<Either<SrpGroup, ClassInstance<CustomGroup>>>> for Group {
+  fn from(group: Option<Either<SrpGroup, ClassInstance<CustomGroup>>>) -> Self {
+    match group {
+      None => Group::default(),
+      Some(Either::A(group)) => Group::Builtin(group),
+      Some(Either::B(custom)) => custom.group.clone(),
+    }
+  }
+}
+
+/// Options for Client and Server instances
+#[napi(object)]
+#[derive(Default)]
+pub struct SrpOptions {
+  /// Hash function (defaults to SHA-256)
+  pub hash: Option<SrpHash>,
+  /// Encoding of inputs and outputs (defaults to hex)
+  pub encoding: Option<SrpEncoding>,
+}
+
+/// Helper function to create SrpGroup from bit size
+#[napi]
+pub fn srp_group_from_value(value: u32) -> Result<SrpGroup> {
+  Ok(SrpGroup::try_from(value)?)
+}
+
+/// Hex-encoded N and g of a group
+#[napi(object)]
+pub struct GroupParameters {
+  pub prime: String,
+  pub generator: String,
+}
+
+/// N and g of a builtin group, e.g. to validate them or share them with peers
+#[napi]
+pub fn group_parameters(group: SrpGroup) -> GroupParameters {
+  let group = Group::Builtin(group);
+  let (N_, g_) = group.constants();
+  GroupParameters {
+    prime: N_.to_hex(),
+    generator: g_.to_hex(),
+  }
+}
@@ -0,0 +1,508 @@
+use crate::error::{Error, SrpError};
+use crate::node::encoding::{take, SrpEncoding};
+use crate::node::params::{CustomGroup, SrpOptions};
+use crate::node::{reject, Result};
+use crate::params::{SrpGroup, SECRET_BYTES};
+use crate::server::{
+  ChallengeStore as SrpChallengeStore, Server as SrpServer, ServerHandshake as SrpServerHandshake,
+};
+use crate::srp_integer::{SecretInteger, SrpInteger};
+use crate::token::check_key;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::time::Duration;
+
+/// Server's ephemeral key pair
+#[napi]
+pub struct ServerEphemeral {
+  pub secret: Either<String, Buffer>,
+  pub public: Either<String, Buffer>,
+}
+
+/// Server's session key and proof
+#[napi(object)]
+pub struct ServerSession {
+  pub key: Either<String, Buffer>,
+  pub proof: Either<String, Buffer>,
+}
+
+/// Server-side SRP implementation
+#[napi]
+#[derive(Clone)]
+pub struct Server {
+  inner: SrpServer,
+  encoding: SrpEncoding,
+}
+
+#[napi]
+impl Server {
+  /// Create a new Server instance with optional parameter group (builtin or custom) and options
+  #[napi(constructor)]
+  pub fn new(
+    group: Option<Either<SrpGroup, ClassInstance<CustomGroup>>>,
+    options: Option<SrpOptions>,
+  ) -> Self {
+    let options = options.unwrap_or_default();
+    Server {
+      inner: SrpServer::new(group, options.hash.unwrap_or_default()),
+      encoding: options.encoding.unwrap_or_default(),
+    }
+  }
+
+  /// Generate server's ephemeral key pair
+  #[napi]
+  pub fn generate_ephemeral(&self, verifier: Either<String, Buffer>) -> Result<ServerEphemeral> {
+    // v    Password verifier
+    let v = self.encoding.integer(&verifier)?;
+
+    let (b, B) = self.inner.ephemeral(&v);
+
+    Ok(ServerEphemeral {
+      secret: self.encoding.encode(&b, SECRET_BYTES),
+      public: self.encoding.encode(&B, self.inner.group.prime_bytes()),
+    })
+  }
+
+  /// Start a handshake that keeps the secret ephemeral and the user record
+  /// to itself, for a single call to finish()
+  #[napi]
+  pub fn start_handshake(
+    &self,
+    username: String,
+    salt: Either<String, Buffer>,
+    verifier: Either<String, Buffer>,
+  ) -> Result<ServerHandshake> {
+    // s    User's salt
+    let s = self.encoding.integer(&salt)?;
+
+    // v    Password verifier
+    let v = self.encoding.integer(&verifier)?;
+
+    let handshake = self.inner.handshake(username, s, v, false);
+    Ok(self.wrap(handshake, salt))
+  }
+
+  /// Start a decoy handshake for a username that has no account. The salt
+  /// and verifier are derived from an HMAC of the username under a server
+  /// secret key of at least 32 bytes, so repeated probes see the same salt.
+  /// finish() does the same work as for a real user and always fails.
+  #[napi]
+  pub fn fake_user_challenge(
+    &self,
+    username: String,
+    server_secret_key: Buffer,
+  ) -> Result<ServerHandshake> {
+    let handshake = self
+      .inner
+      .fake_user_challenge(&username, &server_secret_key)?;
+    let salt = self.encoding.encode(&handshake.s, SECRET_BYTES);
+    Ok(self.wrap(handshake, salt))
+  }
+
+  /// Generate server's ephemeral key pair on the libuv thread pool
+  #[napi(ts_return_type = "Promise<ServerEphemeral>")]
+  pub fn generate_ephemeral_async(
+    &self,
+    verifier: Either<String, Buffer>,
+  ) -> AsyncTask<GenerateServerEphemeralTask> {
+    AsyncTask::new(GenerateServerEphemeralTask {
+      server: self.clone(),
+      verifier,
+    })
+  }
+
+  /// Resume a handshake sealed by ServerHandshake.seal, possibly on another
+  /// node sharing the same key. The verifier is looked up again and must be
+  /// the one the handshake was started with; omit it for a username with
+  /// no account. A token sealed from a decoy resumes either way, and its
+  /// finish() fails like a wrong password. A token resumes as often as it
+  /// is presented until it expires; record the handshake's tokenId as
+  /// spent to allow a single attempt.
+  #[napi]
+  pub fn resume_handshake(
+    &self,
+    token: String,
+    key: Buffer,
+    verifier: Option<Either<String, Buffer>>,
+  ) -> Result<ServerHandshake> {
+    let token = self.inner.open(&token, &key)?;
+
+    // v    Password verifier
+    let v = verifier
+      .map(|verifier| self.encoding.integer(&verifier))
+      .transpose()?;
+
+    let handshake = self.inner.resume(token, &key, v)?;
+    let salt_bytes = handshake.s.to_bytes().len();
+    let salt = self.encoding.encode(&handshake.s, salt_bytes);
+    Ok(self.wrap(handshake, salt))
+  }
+
+  /// Derive the session key and proof on the server side
+  #[napi]
+  pub fn derive_session(
+    &self,
+    server_secret_ephemeral: Either<String, Buffer>,
+    client_public_ephemeral: Either<String, Buffer>,
+    salt: Either<String, Buffer>,
+    username: String,
+    verifier: Either<String, Buffer>,
+    client_session_proof: Either<String, Buffer>,
+  ) -> Result<ServerSession> {
+    // b    Secret ephemeral value
+    let b = self.encoding.secret(server_secret_ephemeral)?;
+
+    // A    Client's public ephemeral value
+    let A = self.encoding.integer(&client_public_ephemeral)?;
+
+    // s    User's salt
+    let s = self.encoding.integer(&salt)?;
+
+    // v    Password verifier
+    let v = self.encoding.integer(&verifier)?;
+
+    // M1   Client's proof of session key
+    let M1_bytes = self.encoding.bytes(&client_session_proof)?;
+
+    // B = kv + g^b
+    let B = self.inner.public(&b, &v);
+
+    let (K, M2) = self
+      .inner
+      .session(&b, &B, &A, &s, &username, &v, &M1_bytes)?;
+    Ok(self.session(&K, &M2))
+  }
+
+  /// Derive the session key and proof on the libuv thread pool
+  #[napi(ts_return_type = "Promise<ServerSession>")]
+  pub fn derive_session_async(
+    &self,
+    server_secret_ephemeral: Either<String, Buffer>,
+    client_public_ephemeral: Either<String, Buffer>,
+    salt: Either<String, Buffer>,
+    username: String,
+    verifier: Either<String, Buffer>,
+    client_session_proof: Either<String, Buffer>,
+  ) -> AsyncTask<DeriveServerSessionTask> {
+    AsyncTask::new(DeriveServerSessionTask {
+      server: self.clone(),
+      server_secret_ephemeral,
+      client_public_ephemeral,
+      salt,
+      username,
+      verifier,
+      client_session_proof,
+    })
+  }
+}
+
+// Only built with the test-rng feature, so release builds never ship it
+#[cfg(feature = "test-rng")]
+#[napi]
+impl Server {
+  /// Create a Server whose ephemerals come from a seeded DRBG.
+  /// For reproducible tests only, never use this in production.
+  #[napi(factory)]
+  pub fn with_test_rng(
+    seed: u32,
+    group: Option<Either<SrpGroup, ClassInstance<CustomGroup>>>,
+    options: Option<SrpOptions>,
+  ) -> Self {
+    let mut server = Server::new(group, options);
+    server.inner.rng = crate::rng::RngSource::seeded(seed);
+    server
+  }
+}
+
+impl Server {
+  fn wrap(&self, handshake: SrpServerHandshake, salt: Either<String, Buffer>) -> ServerHandshake {
+    ServerHandshake {
+      server: self.clone(),
+      salt,
+      public: self
+        .encoding
+        .encode(&handshake.B, self.inner.group.prime_bytes()),
+      token_id: handshake.token_id().map(str::to_string),
+      state: Some(handshake),
+    }
+  }
+
+  fn session(&self, K: &SecretInteger, M2: &SrpInteger) -> ServerSession {
+    ServerSession {
+      key: self.encoding.encode(K, self.inner.hash.output_bytes()),
+      proof: self.encoding.encode(M2, self.inner.hash.output_bytes()),
+    }
+  }
+}
+
+/// Options for a ChallengeStore
+#[napi(object)]
+#[derive(Default)]
+pub struct ChallengeStoreOptions {
+  /// Seconds a challenge can be answered for (defaults to 60)
+  pub ttl_seconds: Option<u32>,
+  /// Maximum number of outstanding challenges; the least recently issued
+  /// ones are evicted first (defaults to 10000)
+  pub capacity: Option<u32>,
+}
+
+/// Server's public ephemeral value, with the id it is stored under
+#[napi(object)]
+pub struct ServerChallenge {
+  pub id: String,
+  pub public: Either<String, Buffer>,
+}
+
+/// In-process store of server ephemerals. Each one expires after a TTL and
+/// is consumed by the first deriveSession attempt, successful or not.
+#[napi]
+pub struct ChallengeStore {
+  server: Server,
+  store: SrpChallengeStore,
+}
+
+#[napi]
+impl ChallengeStore {
+  /// Create a store issuing ephemerals with the server's group, hash
+  /// function and encoding
+  #[napi(constructor)]
+  pub fn new(server: &Server, options: Option<ChallengeStoreOptions>) -> Result<Self> {
+    let options = options.unwrap_or_default();
+    let ttl = options
+      .ttl_seconds
+      .map_or(SrpChallengeStore::DEFAULT_TTL, |ttl| {
+        Duration::from_secs(u64::from(ttl))
+      });
+    let capacity = options
+      .capacity
+      .map_or(SrpChallengeStore::DEFAULT_CAPACITY, |capacity| {
+        capacity as usize
+      });
+
+    Ok(ChallengeStore {
+      server: server.clone(),
+      store: SrpChallengeStore::new(&server.inner, ttl, capacity)?,
+    })
+  }
+
+  /// Number of challenges issued and not yet consumed or evicted
+  #[napi(getter)]
+  pub fn size(&self) -> u32 {
+    self.store.len() as u32
+  }
+
+  /// Generate a server ephemeral and keep its secret half in the store
+  #[napi]
+  pub fn issue(&self, verifier: Either<String, Buffer>) -> Result<ServerChallenge> {
+    // v    Password verifier
+    let v = self.server.encoding.integer(&verifier)?;
+
+    let (id, public) = self.store.put(&v, |B| {
+      self
+        .server
+        .encoding
+        .encode(B, self.server.inner.group.prime_bytes())
+    });
+
+    Ok(ServerChallenge { id, public })
+  }
+
+  /// Derive the session key and proof for an issued challenge, with the
+  /// verifier it was issued for. The challenge is consumed whatever the
+  /// outcome.
+  #[napi]
+  pub fn derive_session(
+    &self,
+    id: String,
+    client_public_ephemeral: Either<String, Buffer>,
+    salt: Either<String, Buffer>,
+    username: String,
+    verifier: Either<String, Buffer>,
+    client_session_proof: Either<String, Buffer>,
+  ) -> Result<ServerSession> {
+    let challenge = self.store.take(&id)?;
+
+    let encoding = self.server.encoding;
+
+    // A    Client's public ephemeral value
+    let A = encoding.integer(&client_public_ephemeral)?;
+
+    // s    User's salt
+    let s = encoding.integer(&salt)?;
+
+    // v    Password verifier
+    let v = encoding.integer(&verifier)?;
+    challenge.check_verifier(&v)?;
+
+    // M1   Client's proof of session key
+    let M1_bytes = encoding.bytes(&client_session_proof)?;
+
+    let (K, M2) =
+      self
+        .server
+        .inner
+        .session(&challenge.b, &challenge.B, &A, &s, &username, &v, &M1_bytes)?;
+    Ok(self.server.session(&K, &M2))
+  }
+}
+
+/// Server side of a single handshake, created by Server.startHandshake
+#[napi]
+pub struct ServerHandshake {
+  server: Server,
+  salt: Either<String, Buffer>,
+  public: Either<String, Buffer>,
+  token_id: Option<String>,
+  state: Option<SrpServerHandshake>,
+}
+
+#[napi]
+impl ServerHandshake {
+  /// Server's public ephemeral value B, to send to the client
+  #[napi(getter)]
+  pub fn public(&self) -> Either<String, Buffer> {
+    self.public.clone()
+  }
+
+  /// User's salt, to send to the client
+  #[napi(getter)]
+  pub fn salt(&self) -> Either<String, Buffer> {
+    self.salt.clone()
+  }
+
+  /// Id of the sealed token this handshake was resumed from, in hex, or
+  /// null if it was started on this server. Tokens can be resumed more
+  /// than once, so record the id to refuse a second attempt.
+  #[napi(getter)]
+  pub fn token_id(&self) -> Option<String> {
+    self.token_id.clone()
+  }
+
+  /// Seal the handshake into an opaque token, encrypted and authenticated
+  /// under a 32-byte server key, to finish it with Server.resumeHandshake.
+  /// The token expires after ttlSeconds (defaults to 60), and this
+  /// handshake can no longer be finished. Until it expires, the token can
+  /// be resumed any number of times.
+  #[napi]
+  pub fn seal(&mut self, key: Buffer, ttl_seconds: Option<u32>) -> Result<String> {
+    // Check the key first, so a bad one leaves the handshake usable
+    check_key(&key)?;
+    let state = self.take_state()?;
+    let ttl = ttl_seconds.map_or(SrpServerHandshake::DEFAULT_TOKEN_TTL, |ttl| {
+      Duration::from_secs(u64::from(ttl))
+    });
+    Ok(state.seal(&key, ttl)?)
+  }
+
+  /// Verify the client's proof and derive the session key and proof.
+  /// The secret ephemeral is dropped whatever the outcome, so a handshake
+  /// can only be finished once.
+  #[napi]
+  pub fn finish(
+    &mut self,
+    client_public_ephemeral: Either<String, Buffer>,
+    client_session_proof: Either<String, Buffer>,
+  ) -> Result<ServerSession> {
+    let state = self.take_state()?;
+    let encoding = self.server.encoding;
+
+    // A    Client's public ephemeral value
+    let A = encoding.integer(&client_public_ephemeral)?;
+
+    // M1   Client's proof of session key
+    let M1_bytes = encoding.bytes(&client_session_proof)?;
+
+    let (K, M2) = state.verify(&A, &M1_bytes)?;
+    Ok(self.server.session(&K, &M2))
+  }
+}
+
+impl ServerHandshake {
+  fn take_state(&mut self) -> Result<SrpServerHandshake> {
+    Ok(self.state.take().ok_or_else(|| {
+      Error::new(
+        SrpError::HandshakeFinished,
+        "Handshake has already been finished",
+      )
+    })?)
+  }
+}
+
+/// Server::generate_ephemeral run off the event loop
+pub struct GenerateServerEphemeralTask {
+  server: Server,
+  verifier: Either<String, Buffer>,
+}
+
+impl Task for GenerateServerEphemeralTask {
+  type Output = Result<ServerEphemeral>;
+  type JsValue = ServerEphemeral;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    Ok(self.server.generate_ephemeral(take(&mut self.verifier)))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    output.map_err(|error| reject(env, error))
+  }
+}
+
+/// Server::derive_session run off the event loop
+pub struct DeriveServerSessionTask {
+  server: Server,
+  server_secret_ephemeral: Either<String, Buffer>,
+  client_public_ephemeral: Either<String, Buffer>,
+  salt: Either<String, Buffer>,
+  username: String,
+  verifier: Either<String, Buffer>,
+  client_session_proof: Either<String, Buffer>,
+}
+
+impl Task for DeriveServerSessionTask {
+  type Output = Result<ServerSession>;
+  type JsValue = ServerSession;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    Ok(self.server.derive_session(
+      take(&mut self.server_secret_ephemeral),
+      take(&mut self.client_public_ephemeral),
+      take(&mut self.salt),
+      std::mem::take(&mut self.username),
+      take(&mut self.verifier),
+      take(&mut self.client_session_proof),
+    ))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    output.map_err(|error| reject(env, error))
+  }
+}
+
+// Standalone functions for backward compatibility
+/// Generate server's ephemeral key pair
+#[napi(js_name = "generateServerEphemeral")]
+pub fn generate_ephemeral(verifier: String) -> Result<ServerEphemeral> {
+  // Create a default server and use its method
+  Server::new(None, None).generate_ephemeral(Either::A(verifier))
+}
+
+/// Derive the session key and proof on the server side
+#[napi(js_name = "deriveServerSession")]
+pub fn derive_session(
+  server_secret_ephemeral: String,
+  client_public_ephemeral: String,
+  salt: String,
+  username: String,
+  verifier: String,
+  client_session_proof: String,
+) -> Result<ServerSession> {
+  // Create a default server and use its method
+  Server::new(None, None).derive_session(
+    Either::A(server_secret_ephemeral),
+    Either::A(client_public_ephemeral),
+    Either::A(salt),
+    username,
+    Either::A(verifier),
+    Either::A(client_session_proof),
+  )
+}
@@ -0,0 +1,26 @@
+use crate::error::{Error, SrpError};
+use crate::node::Result;
+use crate::srp_integer::SrpInteger;
+use crate::validate::{check_group, GroupReport};
+use napi_derive::napi;
+
+/// Options for validateGroup
+#[napi(object)]
+#[derive(Default)]
+pub struct ValidateGroupOptions {
+  /// Minimum bit length of N (defaults to 2048)
+  pub min_bits: Option<u32>,
+}
+
+/// Check that N is a large safe prime and that g generates a large subgroup
+#[napi]
+pub fn validate_group(
+  prime: String,
+  generator: String,
+  options: Option<ValidateGroupOptions>,
+) -> Result<GroupReport> {
+  let N = SrpInteger::from_hex(&prime).map_err(|e| Error::new(SrpError::InvalidHex, e))?;
+  let g = SrpInteger::from_hex(&generator).map_err(|e| Error::new(SrpError::InvalidHex, e))?;
+
+  Ok(check_group(&N, &g, options.unwrap_or_default().min_bits))
+}
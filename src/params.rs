@@ -1,8 +1,16 @@
+use crate::error::{Error, Result, SrpError};
 use crate::srp_integer::SrpInteger;
 use lazy_static::lazy_static;
-use napi::{Error, Result, Status};
+#[cfg(feature = "napi")]
 use napi_derive::napi;
-use sha2::{Digest, Sha256};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::collections::HashMap;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+use zeroize::Zeroizing;
 
 // RFC 5054 SRP parameter groups
 // 1024-bit Group
@@ -25,153 +33,394 @@ const G_3072_HEX: &str = "05";
 const N_4096_HEX: &str = "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E208E24FA074E5AB3143DB5BFCE0FD108E4B82D120A92108011A723C12A787E6D788719A10BDBA5B2699C327186AF4E23C1A946834B6150BDA2583E9CA2AD44CE8DBBBC2DB04DE8EF92E8EFC141FBECAA6287C59474E6BC05D99B2964FA090C3A2233BA186515BE7ED1F612970CEE2D7AFB81BDD762170481CD0069127D5B05AA993B4EA988D8FDDC186FFB7DC90A6C08F4DF435C934063199FFFFFFFFFFFFFFFF";
 const G_4096_HEX: &str = "05";
 
-pub const HASH_OUTPUT_BYTES: usize = 32; // 256 / 8
+// 6144-bit Group
+const N_6144_HEX: &str = "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E208E24FA074E5AB3143DB5BFCE0FD108E4B82D120A92108011A723C12A787E6D788719A10BDBA5B2699C327186AF4E23C1A946834B6150BDA2583E9CA2AD44CE8DBBBC2DB04DE8EF92E8EFC141FBECAA6287C59474E6BC05D99B2964FA090C3A2233BA186515BE7ED1F612970CEE2D7AFB81BDD762170481CD0069127D5B05AA993B4EA988D8FDDC186FFB7DC90A6C08F4DF435C93402849236C3FAB4D27C7026C1D4DCB2602646DEC9751E763DBA37BDF8FF9406AD9E530EE5DB382F413001AEB06A53ED9027D831179727B0865A8918DA3EDBEBCF9B14ED44CE6CBACED4BB1BDB7F1447E6CC254B332051512BD7AF426FB8F401378CD2BF5983CA01C64B92ECF032EA15D1721D03F482D7CE6E74FEF6D55E702F46980C82B5A84031900B1C9E59E7C97FBEC7E8F323A97A7E36CC88BE0F1D45B7FF585AC54BD407B22B4154AACC8F6D7EBF48E1D814CC5ED20F8037E0A79715EEF29BE32806A1D58BB7C5DA76F550AA3D8A1FBFF0EB19CCB1A313D55CDA56C9EC2EF29632387FE8D76E3C0468043E8F663F4860EE12BF2D5B0B7474D6E694F91E6DCC4024FFFFFFFFFFFFFFFF";
+const G_6144_HEX: &str = "05";
+
+// 8192-bit Group
+const N_8192_HEX: &str = "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E208E24FA074E5AB3143DB5BFCE0FD108E4B82D120A92108011A723C12A787E6D788719A10BDBA5B2699C327186AF4E23C1A946834B6150BDA2583E9CA2AD44CE8DBBBC2DB04DE8EF92E8EFC141FBECAA6287C59474E6BC05D99B2964FA090C3A2233BA186515BE7ED1F612970CEE2D7AFB81BDD762170481CD0069127D5B05AA993B4EA988D8FDDC186FFB7DC90A6C08F4DF435C93402849236C3FAB4D27C7026C1D4DCB2602646DEC9751E763DBA37BDF8FF9406AD9E530EE5DB382F413001AEB06A53ED9027D831179727B0865A8918DA3EDBEBCF9B14ED44CE6CBACED4BB1BDB7F1447E6CC254B332051512BD7AF426FB8F401378CD2BF5983CA01C64B92ECF032EA15D1721D03F482D7CE6E74FEF6D55E702F46980C82B5A84031900B1C9E59E7C97FBEC7E8F323A97A7E36CC88BE0F1D45B7FF585AC54BD407B22B4154AACC8F6D7EBF48E1D814CC5ED20F8037E0A79715EEF29BE32806A1D58BB7C5DA76F550AA3D8A1FBFF0EB19CCB1A313D55CDA56C9EC2EF29632387FE8D76E3C0468043E8F663F4860EE12BF2D5B0B7474D6E694F91E6DBE115974A3926F12FEE5E438777CB6A932DF8CD8BEC4D073B931BA3BC832B68D9DD300741FA7BF8AFC47ED2576F6936BA424663AAB639C5AE4F5683423B4742BF1C978238F16CBE39D652DE3FDB8BEFC848AD922222E04A4037C0713EB57A81A23F0C73473FC646CEA306B4BCBC8862F8385DDFA9D4B7FA2C087E879683303ED5BDD3A062B3CF5B3A278A66D2A13F83F44F82DDF310EE074AB6A364597E899A0255DC164F31CC50846851DF9AB48195DED7EA1B1D510BD7EE74D73FAF36BC31ECFA268359046F4EB879F924009438B481C6CD7889A002ED5EE382BC9190DA6FC026E479558E4475677E9AA9E3050E2765694DFC81F56E880B96E7160C980DD98EDD3DFFFFFFFFFFFFFFFFF";
+const G_8192_HEX: &str = "13";
+
+/// Size in bytes of generated salts and secret ephemeral values
+pub const SECRET_BYTES: usize = 32; // 256 / 8
 
 /// Enum representing SRP parameter groups from RFC 5054
-#[napi]
-#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(not(feature = "napi"), derive(Clone, Copy))]
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
 pub enum SrpGroup {
   /// 1024-bit SRP group from RFC 5054
   RFC5054_1024,
   /// 1536-bit SRP group from RFC 5054
   RFC5054_1536,
   /// 2048-bit SRP group from RFC 5054
+  #[default] // Default to the 2048-bit group for backward compatibility
   RFC5054_2048,
   /// 3072-bit SRP group from RFC 5054
   RFC5054_3072,
   /// 4096-bit SRP group from RFC 5054
   RFC5054_4096,
+  /// 6144-bit SRP group from RFC 5054
+  RFC5054_6144,
+  /// 8192-bit SRP group from RFC 5054
+  RFC5054_8192,
 }
 
-// Default to the 2048-bit group for backward compatibility
-impl Default for SrpGroup {
+impl SrpGroup {
+  pub const ALL: [SrpGroup; 7] = [
+    SrpGroup::RFC5054_1024,
+    SrpGroup::RFC5054_1536,
+    SrpGroup::RFC5054_2048,
+    SrpGroup::RFC5054_3072,
+    SrpGroup::RFC5054_4096,
+    SrpGroup::RFC5054_6144,
+    SrpGroup::RFC5054_8192,
+  ];
+}
+
+// A custom group's constants and their derived values for every hash
+pub struct CustomParams {
+  N: SrpInteger,
+  g: SrpInteger,
+  hashed: HashMap<SrpHash, HashedParams>,
+}
+
+/// Parameter group of a Client or Server: a builtin RFC 5054 group or a
+/// caller-supplied prime N and generator g
+#[derive(Clone)]
+pub enum Group {
+  Builtin(SrpGroup),
+  Custom(Arc<CustomParams>),
+}
+
+impl Default for Group {
   fn default() -> Self {
-    SrpGroup::RFC5054_2048
+    Group::Builtin(SrpGroup::default())
+  }
+}
+
+impl From<SrpGroup> for Group {
+  fn from(group: SrpGroup) -> Self {
+    Group::Builtin(group)
+  }
+}
+
+impl Group {
+  /// Create a group from a big-endian prime N and generator g.
+  /// k and H(N) XOR H(g) are computed once here for every hash function
+  /// and variant.
+  pub fn custom(prime: &[u8], generator: &[u8]) -> Result<Self> {
+    Group::custom_from(
+      SrpInteger::from_bytes(prime),
+      SrpInteger::from_bytes(generator),
+    )
+  }
+
+  pub(crate) fn custom_from(N_: SrpInteger, g_: SrpInteger) -> Result<Self> {
+    if !N_.is_odd() {
+      return Err(Error::new(
+        SrpError::InvalidGroup,
+        "Custom group prime must be odd",
+      ));
+    }
+
+    // g must lie in [2, N - 2], like any other group element we accept
+    if let Some(reason) = check_public_ephemeral(&g_, &N_) {
+      return Err(Error::new(
+        SrpError::InvalidGroup,
+        format!("Custom group generator {}", reason),
+      ));
+    }
+
+    let hashed = SrpHash::ALL
+      .into_iter()
+      .map(|hash| (hash, HashedParams::new(hash, &N_, &g_)))
+      .collect();
+
+    Ok(Group::Custom(Arc::new(CustomParams {
+      N: N_,
+      g: g_,
+      hashed,
+    })))
+  }
+
+  /// Big-endian prime N
+  pub fn prime(&self) -> Vec<u8> {
+    self.constants().0.to_bytes()
+  }
+
+  /// Big-endian generator g
+  pub fn generator(&self) -> Vec<u8> {
+    self.constants().1.to_bytes()
+  }
+
+  // N and g
+  pub(crate) fn constants(&self) -> (&SrpInteger, &SrpInteger) {
+    match self {
+      Group::Builtin(group) => get_group_constants(*group),
+      Group::Custom(custom) => (&custom.N, &custom.g),
+    }
+  }
+
+  /// N, g, and k for this group, a hash function and a variant
+  pub(crate) fn params(
+    &self,
+    hash: SrpHash,
+    variant: SrpVariant,
+  ) -> (&SrpInteger, &SrpInteger, &SrpInteger) {
+    match self {
+      Group::Builtin(group) => get_group_params(*group, hash, variant),
+      Group::Custom(custom) => (&custom.N, &custom.g, custom.hashed[&hash].k(variant)),
+    }
+  }
+
+  /// Length of N in bytes, used to pad verifiers and public ephemerals
+  pub fn prime_bytes(&self) -> usize {
+    let (N_, _) = self.constants();
+    N_.bits().div_ceil(8) as usize
+  }
+
+  /// H(N) XOR H(g) for this group and a hash function
+  pub(crate) fn h_N_xor_h_g(&self, hash: SrpHash) -> &SrpInteger {
+    match self {
+      Group::Builtin(group) => get_h_N_xor_h_g(*group, hash),
+      Group::Custom(custom) => &custom.hashed[&hash].h_N_xor_h_g,
+    }
+  }
+}
+
+/// Hash function used for k, u, x, K and the session proofs
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(not(feature = "napi"), derive(Clone, Copy))]
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub enum SrpHash {
+  /// SHA-1, as used by RFC 5054 (TLS-SRP)
+  SHA1,
+  /// SHA-256
+  #[default] // Default to SHA-256 for backward compatibility
+  SHA256,
+  /// SHA-384
+  SHA384,
+  /// SHA-512, as used by Apple-style SRP
+  SHA512,
+}
+
+impl SrpHash {
+  pub const ALL: [SrpHash; 4] = [
+    SrpHash::SHA1,
+    SrpHash::SHA256,
+    SrpHash::SHA384,
+    SrpHash::SHA512,
+  ];
+
+  /// Digest length in bytes
+  pub fn output_bytes(self) -> usize {
+    match self {
+      SrpHash::SHA1 => 20,
+      SrpHash::SHA256 => 32,
+      SrpHash::SHA384 => 48,
+      SrpHash::SHA512 => 64,
+    }
   }
 }
 
-/// Helper function to create SrpGroup from bit size
-#[napi]
-pub fn srp_group_from_value(value: u32) -> Result<SrpGroup> {
-  match value {
-    1024 => Ok(SrpGroup::RFC5054_1024),
-    1536 => Ok(SrpGroup::RFC5054_1536),
-    2048 => Ok(SrpGroup::RFC5054_2048),
-    3072 => Ok(SrpGroup::RFC5054_3072),
-    4096 => Ok(SrpGroup::RFC5054_4096),
-    _ => Err(Error::new(
-      Status::InvalidArg,
-      format!("Invalid SRP group size: {}", value),
-    )),
+/// Flavor of SRP-6a, which only differs in how the multiplier k is derived
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SrpVariant {
+  /// k = H(N, g), as the `secure-remote-password` JS package
+  #[default]
+  SecureRemotePassword,
+  /// k = H(N | PAD(g)), as RFC 5054
+  Rfc5054,
+}
+
+/// Builtin group of a bit size
+impl TryFrom<u32> for SrpGroup {
+  type Error = Error;
+
+  fn try_from(value: u32) -> Result<Self> {
+    match value {
+      1024 => Ok(SrpGroup::RFC5054_1024),
+      1536 => Ok(SrpGroup::RFC5054_1536),
+      2048 => Ok(SrpGroup::RFC5054_2048),
+      3072 => Ok(SrpGroup::RFC5054_3072),
+      4096 => Ok(SrpGroup::RFC5054_4096),
+      6144 => Ok(SrpGroup::RFC5054_6144),
+      8192 => Ok(SrpGroup::RFC5054_8192),
+      _ => Err(Error::new(
+        SrpError::UnknownGroup,
+        format!("Invalid SRP group size: {}", value),
+      )),
+    }
   }
 }
 
+// Group values that depend on the hash function
+struct HashedParams {
+  k: SrpInteger,
+  k_padded: SrpInteger,
+  h_N_xor_h_g: SrpInteger,
+}
+
 // Static SRP parameters for all groups
 lazy_static! {
   // Core parameters for the default 2048-bit group
   pub static ref N: SrpInteger = SrpInteger::from_hex(N_2048_HEX).unwrap();
   pub static ref g: SrpInteger = SrpInteger::from_hex(G_2048_HEX).unwrap();
-  pub static ref k: SrpInteger = H(&[&N, &g]);
-
-  // Pre-computed hash values for performance
-  pub static ref h_N: SrpInteger = H(&[&N]);
-  pub static ref h_g: SrpInteger = H(&[&g]);
-  pub static ref h_N_xor_h_g: SrpInteger = h_N.xor(&h_g);
 
   // Parameters for all groups
   pub static ref N_1024: SrpInteger = SrpInteger::from_hex(N_1024_HEX).unwrap();
   pub static ref g_1024: SrpInteger = SrpInteger::from_hex(G_1024_HEX).unwrap();
-  pub static ref k_1024: SrpInteger = H(&[&N_1024, &g_1024]);
 
   pub static ref N_1536: SrpInteger = SrpInteger::from_hex(N_1536_HEX).unwrap();
   pub static ref g_1536: SrpInteger = SrpInteger::from_hex(G_1536_HEX).unwrap();
-  pub static ref k_1536: SrpInteger = H(&[&N_1536, &g_1536]);
 
   pub static ref N_2048: SrpInteger = SrpInteger::from_hex(N_2048_HEX).unwrap();
   pub static ref g_2048: SrpInteger = SrpInteger::from_hex(G_2048_HEX).unwrap();
-  pub static ref k_2048: SrpInteger = H(&[&N_2048, &g_2048]);
 
   pub static ref N_3072: SrpInteger = SrpInteger::from_hex(N_3072_HEX).unwrap();
   pub static ref g_3072: SrpInteger = SrpInteger::from_hex(G_3072_HEX).unwrap();
-  pub static ref k_3072: SrpInteger = H(&[&N_3072, &g_3072]);
 
   pub static ref N_4096: SrpInteger = SrpInteger::from_hex(N_4096_HEX).unwrap();
   pub static ref g_4096: SrpInteger = SrpInteger::from_hex(G_4096_HEX).unwrap();
-  pub static ref k_4096: SrpInteger = H(&[&N_4096, &g_4096]);
 
-  // Precomputed hash values for each group for performance
-  pub static ref h_N_1024: SrpInteger = H(&[&N_1024]);
-  pub static ref h_g_1024: SrpInteger = H(&[&g_1024]);
-  pub static ref h_N_xor_h_g_1024: SrpInteger = h_N_1024.xor(&h_g_1024);
+  pub static ref N_6144: SrpInteger = SrpInteger::from_hex(N_6144_HEX).unwrap();
+  pub static ref g_6144: SrpInteger = SrpInteger::from_hex(G_6144_HEX).unwrap();
+
+  pub static ref N_8192: SrpInteger = SrpInteger::from_hex(N_8192_HEX).unwrap();
+  pub static ref g_8192: SrpInteger = SrpInteger::from_hex(G_8192_HEX).unwrap();
 
-  pub static ref h_N_1536: SrpInteger = H(&[&N_1536]);
-  pub static ref h_g_1536: SrpInteger = H(&[&g_1536]);
-  pub static ref h_N_xor_h_g_1536: SrpInteger = h_N_1536.xor(&h_g_1536);
+  // Precomputed k of both variants and H(N) XOR H(g) for every (group, hash) pair
+  static ref HASHED_PARAMS: HashMap<(SrpGroup, SrpHash), HashedParams> = {
+    let mut params = HashMap::new();
+    for group in SrpGroup::ALL {
+      let (N_, g_) = get_group_constants(group);
+      for hash in SrpHash::ALL {
+        params.insert((group, hash), HashedParams::new(hash, N_, g_));
+      }
+    }
+    params
+  };
+}
 
-  pub static ref h_N_2048: SrpInteger = H(&[&N_2048]);
-  pub static ref h_g_2048: SrpInteger = H(&[&g_2048]);
-  pub static ref h_N_xor_h_g_2048: SrpInteger = h_N_2048.xor(&h_g_2048);
+impl HashedParams {
+  fn k(&self, variant: SrpVariant) -> &SrpInteger {
+    match variant {
+      SrpVariant::SecureRemotePassword => &self.k,
+      SrpVariant::Rfc5054 => &self.k_padded,
+    }
+  }
 
-  pub static ref h_N_3072: SrpInteger = H(&[&N_3072]);
-  pub static ref h_g_3072: SrpInteger = H(&[&g_3072]);
-  pub static ref h_N_xor_h_g_3072: SrpInteger = h_N_3072.xor(&h_g_3072);
+  fn new(hash: SrpHash, N_: &SrpInteger, g_: &SrpInteger) -> Self {
+    let h_N = H(hash, &[N_]);
+    let h_g = H(hash, &[g_]);
+    // PAD(g): g left-padded to the length of N
+    let N_bytes = N_.bits().div_ceil(8) as usize;
+    let g_padded = SrpInteger::from_bytes(&g_.to_padded_bytes(N_bytes));
+    HashedParams {
+      k: H(hash, &[N_, g_]),
+      k_padded: H(hash, &[N_, &g_padded]),
+      h_N_xor_h_g: h_N.xor(&h_g),
+    }
+  }
+}
 
-  pub static ref h_N_4096: SrpInteger = H(&[&N_4096]);
-  pub static ref h_g_4096: SrpInteger = H(&[&g_4096]);
-  pub static ref h_N_xor_h_g_4096: SrpInteger = h_N_4096.xor(&h_g_4096);
+// N and g for a specific group
+fn get_group_constants(group: SrpGroup) -> (&'static SrpInteger, &'static SrpInteger) {
+  match group {
+    SrpGroup::RFC5054_1024 => (&N_1024, &g_1024),
+    SrpGroup::RFC5054_1536 => (&N_1536, &g_1536),
+    SrpGroup::RFC5054_2048 => (&N_2048, &g_2048),
+    SrpGroup::RFC5054_3072 => (&N_3072, &g_3072),
+    SrpGroup::RFC5054_4096 => (&N_4096, &g_4096),
+    SrpGroup::RFC5054_6144 => (&N_6144, &g_6144),
+    SrpGroup::RFC5054_8192 => (&N_8192, &g_8192),
+  }
 }
 
-/// Function to get N, g, and k for a specific group
+/// Function to get N, g, and k for a specific group, hash and variant
 pub fn get_group_params(
   group: SrpGroup,
+  hash: SrpHash,
+  variant: SrpVariant,
 ) -> (
   &'static SrpInteger,
   &'static SrpInteger,
   &'static SrpInteger,
 ) {
-  match group {
-    SrpGroup::RFC5054_1024 => (&N_1024, &g_1024, &k_1024),
-    SrpGroup::RFC5054_1536 => (&N_1536, &g_1536, &k_1536),
-    SrpGroup::RFC5054_2048 => (&N_2048, &g_2048, &k_2048),
-    SrpGroup::RFC5054_3072 => (&N_3072, &g_3072, &k_3072),
-    SrpGroup::RFC5054_4096 => (&N_4096, &g_4096, &k_4096),
-  }
+  let (N_, g_) = get_group_constants(group);
+  (N_, g_, HASHED_PARAMS[&(group, hash)].k(variant))
 }
 
-/// Function to get the precomputed h_N_xor_h_g value for a specific group
-pub fn get_h_N_xor_h_g(group: SrpGroup) -> &'static SrpInteger {
-  match group {
-    SrpGroup::RFC5054_1024 => &h_N_xor_h_g_1024,
-    SrpGroup::RFC5054_1536 => &h_N_xor_h_g_1536,
-    SrpGroup::RFC5054_2048 => &h_N_xor_h_g_2048,
-    SrpGroup::RFC5054_3072 => &h_N_xor_h_g_3072,
-    SrpGroup::RFC5054_4096 => &h_N_xor_h_g_4096,
-  }
+/// Function to get the precomputed h_N_xor_h_g value for a specific group and hash
+pub fn get_h_N_xor_h_g(group: SrpGroup, hash: SrpHash) -> &'static SrpInteger {
+  &HASHED_PARAMS[&(group, hash)].h_N_xor_h_g
 }
 
-/// Hash function for SRP protocol (SHA-256)
-pub fn H(args: &[&SrpInteger]) -> SrpInteger {
-  let mut hasher = Sha256::new();
+// Hash a sequence of byte strings with the given digest
+fn digest<D: Digest>(parts: &[&[u8]]) -> SrpInteger {
+  let mut hasher = D::new();
 
-  for arg in args {
-    let hex = arg.to_hex();
-    let bytes = hex::decode(&hex).unwrap();
-    hasher.update(&bytes);
+  for part in parts {
+    hasher.update(part);
   }
 
   let result = hasher.finalize();
-  let hex_result = hex::encode(result);
+  SrpInteger::from_bytes(&result)
+}
+
+fn digest_with(hash: SrpHash, parts: &[&[u8]]) -> SrpInteger {
+  match hash {
+    SrpHash::SHA1 => digest::<Sha1>(parts),
+    SrpHash::SHA256 => digest::<Sha256>(parts),
+    SrpHash::SHA384 => digest::<Sha384>(parts),
+    SrpHash::SHA512 => digest::<Sha512>(parts),
+  }
+}
 
-  SrpInteger::from_hex(&hex_result).unwrap()
+/// Hash function for SRP protocol
+pub fn H(hash: SrpHash, args: &[&SrpInteger]) -> SrpInteger {
+  // Arguments are often secret (S, x, ...), so wipe the serialized copies
+  let bytes: Vec<Zeroizing<Vec<u8>>> = args
+    .iter()
+    .map(|arg| Zeroizing::new(arg.to_bytes()))
+    .collect();
+  let parts: Vec<&[u8]> = bytes.iter().map(|b| b.as_slice()).collect();
+
+  digest_with(hash, &parts)
 }
 
 // String hashing function
-pub fn H_str(s: &str) -> SrpInteger {
-  let mut hasher = Sha256::new();
-  hasher.update(s.as_bytes());
+pub fn H_str(hash: SrpHash, s: &str) -> SrpInteger {
+  digest_with(hash, &[s.as_bytes()])
+}
 
-  let result = hasher.finalize();
-  let hex_result = hex::encode(result);
+/// Constant-time check of a received proof against the expected one.
+/// Proofs that are not exactly one hash output long never match.
+pub fn proof_matches(expected: &SrpInteger, proof: &[u8], hash: SrpHash) -> bool {
+  if proof.len() != hash.output_bytes() {
+    return false;
+  }
 
-  SrpInteger::from_hex(&hex_result).unwrap()
+  expected.to_bytes().ct_eq(proof).into()
+}
+
+/// SRP-6a safeguard for a received public ephemeral value (A or B).
+/// Values must lie in [2, N - 2]: 0 and anything >= N are rejected rather
+/// than reduced, and 1 and N - 1 only generate the order-2 subgroup.
+/// Returns why the value was rejected, if it was.
+pub fn check_public_ephemeral(value: &SrpInteger, modulus: &SrpInteger) -> Option<&'static str> {
+  let one = SrpInteger::from_bytes(&[1]);
+
+  if value.is_zero() {
+    Some("is invalid")
+  } else if !value.is_less_than(modulus) {
+    Some("is out of range")
+  } else if value.equals(&one) || value.add(&one).equals(modulus) {
+    Some("is in a small subgroup")
+  } else {
+    None
+  }
 }
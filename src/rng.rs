@@ -0,0 +1,51 @@
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "test-rng")]
+use {rand::SeedableRng, rand_chacha::ChaCha20Rng};
+
+/// Source of randomness for salts and secret ephemerals
+pub trait SrpRng: Send {
+  fn fill(&mut self, dest: &mut [u8]);
+}
+
+impl<R: RngCore + CryptoRng + Send> SrpRng for R {
+  fn fill(&mut self, dest: &mut [u8]) {
+    self.fill_bytes(dest);
+  }
+}
+
+/// Randomness source owned by a Client or Server instance, and shared with
+/// the async tasks it spawns
+#[derive(Clone)]
+pub struct RngSource(Arc<Mutex<Box<dyn SrpRng>>>);
+
+impl RngSource {
+  pub fn new<R: SrpRng + 'static>(rng: R) -> Self {
+    Self(Arc::new(Mutex::new(Box::new(rng))))
+  }
+
+  // Default source, backed by the OS CSPRNG
+  pub fn os() -> Self {
+    Self::new(OsRng)
+  }
+
+  // Deterministic ChaCha20 DRBG, only meant for reproducible tests
+  #[cfg(feature = "test-rng")]
+  pub fn seeded(seed: u32) -> Self {
+    Self::new(ChaCha20Rng::seed_from_u64(seed as u64))
+  }
+
+  pub fn fill(&self, dest: &mut [u8]) {
+    self
+      .0
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .fill(dest);
+  }
+}
+
+impl Default for RngSource {
+  fn default() -> Self {
+    Self::os()
+  }
+}
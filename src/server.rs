@@ -1,170 +1,665 @@
-use crate::params::{get_group_params, get_h_N_xor_h_g, H_str, SrpGroup, H, HASH_OUTPUT_BYTES};
-use crate::srp_integer::SrpInteger;
-use napi::bindgen_prelude::*;
-use napi_derive::napi;
+use crate::error::{Error, Result, SrpError};
+use crate::params::{
+  check_public_ephemeral, proof_matches, Group, H_str, SrpHash, SrpVariant, H, SECRET_BYTES,
+};
+use crate::rng::{RngSource, SrpRng};
+use crate::srp_integer::{SecretInteger, SrpInteger};
+use crate::token::{group_fingerprint, now, verifier_fingerprint, HandshakeToken, TOKEN_ID_BYTES};
+use hmac::{Hmac, Mac};
+use lru::LruCache;
+use sha2::Sha256;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
 
 /// Server's ephemeral key pair
-#[napi]
 pub struct ServerEphemeral {
-  pub secret: String,
-  pub public: String,
+  pub secret: Zeroizing<Vec<u8>>,
+  pub public: Vec<u8>,
 }
 
 /// Server's session key and proof
-#[napi(object)]
 pub struct ServerSession {
-  pub key: String,
-  pub proof: String,
+  pub key: Zeroizing<Vec<u8>>,
+  pub proof: Vec<u8>,
 }
 
-/// Server-side SRP implementation
-#[napi]
+/// Server-side SRP implementation.
+/// Values are big-endian byte strings, padded like the Client's.
+#[derive(Clone)]
 pub struct Server {
-  group: SrpGroup,
+  pub(crate) group: Group,
+  pub(crate) hash: SrpHash,
+  pub(crate) variant: SrpVariant,
+  pub(crate) rng: RngSource,
+}
+
+impl Default for Server {
+  fn default() -> Self {
+    Server::new(Group::default(), SrpHash::default())
+  }
 }
 
-#[napi]
 impl Server {
-  /// Create a new Server instance with optional parameter group
-  #[napi(constructor)]
-  pub fn new(group: Option<SrpGroup>) -> Self {
+  /// Create a Server for a parameter group and hash function, drawing
+  /// ephemerals from the OS CSPRNG
+  pub fn new(group: impl Into<Group>, hash: SrpHash) -> Self {
     Server {
-      group: group.unwrap_or_default(),
+      group: group.into(),
+      hash,
+      variant: SrpVariant::default(),
+      rng: RngSource::os(),
     }
   }
 
-  /// Generate server's ephemeral key pair
-  #[napi]
-  pub fn generate_ephemeral(&self, verifier: String) -> Result<ServerEphemeral> {
-    // N    A large safe prime
-    // g    A generator modulo N
-    // k    Multiplier parameter (k = H(N, g))
-    let (N, g, k) = get_group_params(self.group);
+  /// Draw ephemerals from another source, e.g. a seeded DRBG in tests
+  pub fn with_rng(self, rng: impl SrpRng + 'static) -> Self {
+    Server {
+      rng: RngSource::new(rng),
+      ..self
+    }
+  }
 
-    // v    Password verifier
-    let v = SrpInteger::from_hex(&verifier).map_err(|e| Error::new(Status::InvalidArg, e))?;
+  /// Derive k as another flavor of SRP-6a, e.g. RFC 5054
+  pub fn with_variant(self, variant: SrpVariant) -> Self {
+    Server { variant, ..self }
+  }
 
-    // B = kv + g^b (b = random number)
-    let b = SrpInteger::random_integer(HASH_OUTPUT_BYTES);
-    let gb = g.mod_pow(&b, N);
-    let kv = k.multiply(&v).modulo(N);
-    let B = kv.add(&gb).modulo(N);
+  /// Parameter group
+  pub fn group(&self) -> &Group {
+    &self.group
+  }
 
-    Ok(ServerEphemeral {
-      secret: b.to_hex(),
-      public: B.to_hex(),
-    })
+  /// Hash function
+  pub fn hash(&self) -> SrpHash {
+    self.hash
+  }
+
+  /// Flavor of SRP-6a
+  pub fn variant(&self) -> SrpVariant {
+    self.variant
+  }
+
+  /// Generate server's ephemeral key pair
+  pub fn generate_ephemeral(&self, verifier: &[u8]) -> ServerEphemeral {
+    let (b, B) = self.ephemeral(&SrpInteger::from_bytes(verifier));
+
+    ServerEphemeral {
+      secret: Zeroizing::new(b.to_padded_bytes(SECRET_BYTES)),
+      public: B.to_padded_bytes(self.group.prime_bytes()),
+    }
   }
 
   /// Derive the session key and proof on the server side
-  #[napi]
   pub fn derive_session(
     &self,
-    server_secret_ephemeral: String,
-    client_public_ephemeral: String,
-    salt: String,
-    username: String,
-    verifier: String,
-    client_session_proof: String,
+    server_secret_ephemeral: &[u8],
+    client_public_ephemeral: &[u8],
+    salt: &[u8],
+    username: &str,
+    verifier: &[u8],
+    client_session_proof: &[u8],
   ) -> Result<ServerSession> {
+    let b = SecretInteger::new(SrpInteger::from_bytes(server_secret_ephemeral));
+    let v = SrpInteger::from_bytes(verifier);
+    let B = self.public(&b, &v);
+
+    let (K, M2) = self.session(
+      &b,
+      &B,
+      &SrpInteger::from_bytes(client_public_ephemeral),
+      &SrpInteger::from_bytes(salt),
+      username,
+      &v,
+      client_session_proof,
+    )?;
+
+    Ok(self.encode_session(&K, &M2))
+  }
+
+  /// Start a handshake that keeps the secret ephemeral and the user record
+  /// to itself, for a single call to finish()
+  pub fn start_handshake(&self, username: &str, salt: &[u8], verifier: &[u8]) -> ServerHandshake {
+    self.handshake(
+      username.to_string(),
+      SrpInteger::from_bytes(salt),
+      SrpInteger::from_bytes(verifier),
+      false,
+    )
+  }
+
+  /// Start a decoy handshake for a username that has no account. The salt
+  /// and verifier are derived from an HMAC of the username under a server
+  /// secret key of at least 32 bytes, so repeated probes see the same salt.
+  /// finish() does the same work as for a real user and always fails.
+  pub fn fake_user_challenge(
+    &self,
+    username: &str,
+    server_secret_key: &[u8],
+  ) -> Result<ServerHandshake> {
+    if server_secret_key.len() < DECOY_KEY_MIN_BYTES {
+      return Err(Error::new(
+        SrpError::InvalidKey,
+        format!(
+          "Server secret key must be at least {} bytes",
+          DECOY_KEY_MIN_BYTES
+        ),
+      ));
+    }
+
+    // s    Fake salt, as long as the ones from generateSalt
+    let s = SrpInteger::from_bytes(&decoy_hmac(server_secret_key, b"salt", username));
+
+    // v    Fake verifier
+    let v = self.decoy_verifier(server_secret_key, username);
+
+    Ok(self.handshake(username.to_string(), s, v, true))
+  }
+
+  /// Resume a handshake sealed by ServerHandshake::seal, possibly on another
+  /// node sharing the same key. The verifier is looked up again and must be
+  /// the one the handshake was started with; pass None for a username with
+  /// no account. A token sealed from a decoy resumes either way, and its
+  /// finish() fails like a wrong password.
+  ///
+  /// A token is not single-use: it resumes as often as it is presented
+  /// until it expires, each time giving the client another guess against
+  /// the same b and B. Callers that need single use must record the
+  /// resumed handshake's token_id() as spent until the token expires.
+  pub fn resume_handshake(
+    &self,
+    token: &str,
+    key: &[u8],
+    verifier: Option<&[u8]>,
+  ) -> Result<ServerHandshake> {
+    let token = self.open(token, key)?;
+    self.resume(token, key, verifier.map(SrpInteger::from_bytes))
+  }
+}
+
+impl Server {
+  // b (random) and B = kv + g^b for a verifier
+  pub(crate) fn ephemeral(&self, v: &SrpInteger) -> (SecretInteger, SrpInteger) {
+    // b    Secret ephemeral value
+    let b = SecretInteger::random_integer(&self.rng, SECRET_BYTES);
+
+    let B = self.public(&b, v);
+
+    (b, B)
+  }
+
+  // Fake verifier for a decoy, expanded from an HMAC to the length of N and
+  // reduced mod N. No exponentiation, so a decoy costs the same as a real
+  // handshake: B = kv + g^b is uniform whatever v is.
+  pub(crate) fn decoy_verifier(&self, key: &[u8], username: &str) -> SrpInteger {
+    // N    A large safe prime
+    let (N, _) = self.group.constants();
+
+    let bytes = decoy_expand(key, b"verifier", username, self.group.prime_bytes());
+    SrpInteger::from_bytes(&bytes).modulo(N)
+  }
+
+  // B = kv + g^b
+  pub(crate) fn public(&self, b: &SecretInteger, v: &SrpInteger) -> SrpInteger {
     // N    A large safe prime
     // g    A generator modulo N
-    let (N, g, k) = get_group_params(self.group);
+    // k    Multiplier parameter (k = H(N, g), or H(N | PAD(g)) in RFC 5054)
+    let (N, g, k) = self.group.params(self.hash, self.variant);
 
-    // b    Secret ephemeral value
-    let b = SrpInteger::from_hex(&server_secret_ephemeral)
-      .map_err(|e| Error::new(Status::InvalidArg, e))?;
+    g.add_mult_pow(k, v, g, b, N)
+  }
 
-    // A    Client's public ephemeral value
-    let A = SrpInteger::from_hex(&client_public_ephemeral)
-      .map_err(|e| Error::new(Status::InvalidArg, e))?;
+  // New b and B for a handshake
+  pub(crate) fn handshake(
+    &self,
+    username: String,
+    s: SrpInteger,
+    v: SrpInteger,
+    decoy: bool,
+  ) -> ServerHandshake {
+    let (b, B) = self.ephemeral(&v);
 
-    // s    User's salt
-    let s = SrpInteger::from_hex(&salt).map_err(|e| Error::new(Status::InvalidArg, e))?;
+    ServerHandshake {
+      server: self.clone(),
+      username,
+      s,
+      v,
+      b,
+      B,
+      decoy,
+      token_id: None,
+    }
+  }
 
-    // v    Password verifier
-    let v = SrpInteger::from_hex(&verifier).map_err(|e| Error::new(Status::InvalidArg, e))?;
+  // Decrypt a sealed handshake and check that it expects this server
+  pub(crate) fn open(&self, token: &str, key: &[u8]) -> Result<HandshakeToken> {
+    let token = HandshakeToken::open(token, key)?;
 
-    // I    Username
-    let I = username;
+    if now() >= token.expires {
+      return Err(Error::new(
+        SrpError::TokenExpired,
+        "Handshake token has expired",
+      ));
+    }
+
+    if token.group != group_fingerprint(&self.group, self.hash, self.variant) {
+      return Err(Error::new(
+        SrpError::TokenMismatch,
+        "Handshake token was sealed for a different group or hash function",
+      ));
+    }
+
+    Ok(token)
+  }
+
+  // Handshake of an opened token, for the verifier it was sealed with. A
+  // decoy has no verifier to look up, so whatever the caller passes is
+  // ignored and a fake one is derived from the token key again.
+  pub(crate) fn resume(
+    &self,
+    token: HandshakeToken,
+    key: &[u8],
+    v: Option<SrpInteger>,
+  ) -> Result<ServerHandshake> {
+    let v = if token.decoy {
+      self.decoy_verifier(key, &token.username)
+    } else {
+      match v {
+        Some(v) if bool::from(token.verifier.ct_eq(&verifier_fingerprint(&v))) => v,
+        _ => {
+          return Err(Error::new(
+            SrpError::TokenMismatch,
+            "Verifier does not match the handshake token",
+          ))
+        }
+      }
+    };
 
-    // M1   Client's proof of session key
-    let M1 =
-      SrpInteger::from_hex(&client_session_proof).map_err(|e| Error::new(Status::InvalidArg, e))?;
+    Ok(ServerHandshake {
+      server: self.clone(),
+      username: token.username,
+      s: token.s,
+      v,
+      b: token.b,
+      B: token.B,
+      decoy: token.decoy,
+      token_id: Some(hex::encode(token.id)),
+    })
+  }
+
+  // Check the client's A and M1 against the server's b and B, and derive
+  // the session key K and server proof M2
+  #[allow(clippy::too_many_arguments)]
+  pub(crate) fn session(
+    &self,
+    b: &SecretInteger,
+    B: &SrpInteger,
+    A: &SrpInteger,
+    s: &SrpInteger,
+    I: &str,
+    v: &SrpInteger,
+    M1_bytes: &[u8],
+  ) -> Result<(SecretInteger, SrpInteger)> {
+    // N    A large safe prime
+    let (N, _) = self.group.constants();
 
-    // Safeguard against malicious A values (A % N should not be 0)
-    if A.is_zero() || A.modulo(N).is_zero() {
+    // Safeguard against malicious A values (must lie in [2, N - 2])
+    if let Some(reason) = check_public_ephemeral(A, N) {
       return Err(Error::new(
-        Status::InvalidArg,
-        "Client's public ephemeral value is invalid".to_string(),
+        SrpError::InvalidPublicEphemeral,
+        format!("Client's public ephemeral value {}", reason),
       ));
     }
 
-    // B = kv + g^b
-    let B = g.add_mult_pow(k, &v, g, &b, N);
     // u = H(A, B)
-    let u = H(&[&A, &B]);
+    let u = H(self.hash, &[A, B]);
+
+    // Abort if u == 0
+    if u.is_zero() {
+      return Err(Error::new(
+        SrpError::ZeroScrambler,
+        "Scrambling parameter is zero",
+      ));
+    }
 
     // S = (A * v^u) ^ b
-    let vu = v.mod_pow(&u, N);
-    let Avu = A.multiply(&vu).modulo(N);
-    let S = Avu.mod_pow(&b, N);
+    let vu = SecretInteger::new(v.mod_pow_vartime(&u, N));
+    let Avu = SecretInteger::new(A.multiply(&vu).modulo(N));
+    let S = SecretInteger::new(Avu.mod_pow(b, N));
 
     // K = H(S)
-    let K = H(&[&S]);
+    let K = SecretInteger::new(H(self.hash, &[&S]));
 
     // Get hashed value of identity
-    let I_hash = H_str(&I);
+    let I_hash = H_str(self.hash, I);
 
     // Get XOR of hash(N) and hash(g)
-    let N_g_xor = get_h_N_xor_h_g(self.group);
+    let N_g_xor = self.group.h_N_xor_h_g(self.hash);
 
     // Verify that M1 = H(H(N) XOR H(g), H(I), s, A, B, K)
-    let expected_M1 = H(&[N_g_xor, &I_hash, &s, &A, &B, &K]);
+    let expected_M1 = H(self.hash, &[N_g_xor, &I_hash, s, A, B, &K]);
 
-    if !expected_M1.equals(&M1) {
+    if !proof_matches(&expected_M1, M1_bytes, self.hash) {
       return Err(Error::new(
-        Status::GenericFailure,
-        "Client's proof is invalid".to_string(),
+        SrpError::BadClientProof,
+        "Client's proof is invalid",
       ));
     }
 
     // Generate server's proof
     // M2 = H(A, M1, K)
-    let M2 = H(&[&A, &M1, &K]);
+    let M1 = SrpInteger::from_bytes(M1_bytes);
+    let M2 = H(self.hash, &[A, &M1, &K]);
 
-    Ok(ServerSession {
-      key: K.to_hex(),
-      proof: M2.to_hex(),
-    })
+    Ok((K, M2))
   }
+
+  fn encode_session(&self, K: &SecretInteger, M2: &SrpInteger) -> ServerSession {
+    ServerSession {
+      key: Zeroizing::new(K.to_padded_bytes(self.hash.output_bytes())),
+      proof: M2.to_padded_bytes(self.hash.output_bytes()),
+    }
+  }
+}
+
+// Shortest server secret key accepted by fake_user_challenge
+const DECOY_KEY_MIN_BYTES: usize = 32;
+
+// HMAC-SHA256 keyed for label || 0 || username, before any further input
+fn decoy_mac(key: &[u8], label: &[u8], username: &str) -> Hmac<Sha256> {
+  let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+  mac.update(label);
+  mac.update(&[0]);
+  mac.update(username.as_bytes());
+  mac
 }
 
-// Standalone functions for backward compatibility
-/// Generate server's ephemeral key pair
-#[napi(js_name = "generateServerEphemeral")]
-pub fn generate_ephemeral(verifier: String) -> Result<ServerEphemeral> {
-  // Create a default server and use its method
-  Server::new(None).generate_ephemeral(verifier)
+// HMAC-SHA256(key, label || 0 || username), for the decoy salt
+fn decoy_hmac(key: &[u8], label: &[u8], username: &str) -> Zeroizing<Vec<u8>> {
+  let mac = decoy_mac(key, label, username);
+  Zeroizing::new(mac.finalize().into_bytes().to_vec())
+}
+
+// `len` bytes of HMAC-SHA256(key, label || 0 || username || counter) for
+// counter = 0, 1, ..., for the decoy verifier
+fn decoy_expand(key: &[u8], label: &[u8], username: &str, len: usize) -> Zeroizing<Vec<u8>> {
+  let mut out = Zeroizing::new(Vec::with_capacity(len + 32));
+  let mut counter = 0u32;
+  while out.len() < len {
+    let mut mac = decoy_mac(key, label, username);
+    mac.update(&counter.to_be_bytes());
+    out.extend_from_slice(&mac.finalize().into_bytes());
+    counter += 1;
+  }
+  out.truncate(len);
+  out
 }
 
-/// Derive the session key and proof on the server side
-#[napi(js_name = "deriveServerSession")]
-pub fn derive_session(
-  server_secret_ephemeral: String,
-  client_public_ephemeral: String,
-  salt: String,
+/// Server side of a single handshake, created by Server::start_handshake
+pub struct ServerHandshake {
+  server: Server,
   username: String,
-  verifier: String,
-  client_session_proof: String,
-) -> Result<ServerSession> {
-  // Create a default server and use its method
-  Server::new(None).derive_session(
-    server_secret_ephemeral,
-    client_public_ephemeral,
-    salt,
-    username,
-    verifier,
-    client_session_proof,
-  )
+  pub(crate) s: SrpInteger,
+  v: SrpInteger,
+  b: SecretInteger,
+  pub(crate) B: SrpInteger,
+  // Started by fake_user_challenge, so finish() must fail
+  decoy: bool,
+  // Id of the token this handshake was resumed from
+  token_id: Option<String>,
+}
+
+impl ServerHandshake {
+  /// Lifetime of a sealed token unless the caller picks another one
+  pub const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+  /// Server's public ephemeral value B, to send to the client
+  pub fn public(&self) -> Vec<u8> {
+    self.B.to_padded_bytes(self.server.group.prime_bytes())
+  }
+
+  /// User's salt, to send to the client
+  pub fn salt(&self) -> Vec<u8> {
+    self.s.to_bytes()
+  }
+
+  /// Id of the sealed token this handshake was resumed from, in hex, or
+  /// None if it was started on this server. Tokens can be resumed more
+  /// than once, so record the id to refuse a second attempt.
+  pub fn token_id(&self) -> Option<&str> {
+    self.token_id.as_deref()
+  }
+
+  /// Seal the handshake into an opaque token, encrypted and authenticated
+  /// under a 32-byte server key, to finish it with Server::resume_handshake.
+  /// The token expires after `ttl`, and until then it can be resumed any
+  /// number of times; see Server::resume_handshake.
+  pub fn seal(self, key: &[u8], ttl: Duration) -> Result<String> {
+    let mut id = [0u8; TOKEN_ID_BYTES];
+    self.server.rng.fill(&mut id);
+
+    let token = HandshakeToken {
+      id,
+      decoy: self.decoy,
+      group: group_fingerprint(&self.server.group, self.server.hash, self.server.variant),
+      expires: now() + ttl.as_secs(),
+      verifier: verifier_fingerprint(&self.v),
+      username: self.username,
+      s: self.s,
+      b: self.b,
+      B: self.B,
+    };
+    token.seal(key, &self.server.rng)
+  }
+
+  /// Verify the client's proof and derive the session key and proof
+  pub fn finish(
+    self,
+    client_public_ephemeral: &[u8],
+    client_session_proof: &[u8],
+  ) -> Result<ServerSession> {
+    let server = self.server.clone();
+    let (K, M2) = self.verify(
+      &SrpInteger::from_bytes(client_public_ephemeral),
+      client_session_proof,
+    )?;
+    Ok(server.encode_session(&K, &M2))
+  }
+
+  pub(crate) fn verify(
+    self,
+    A: &SrpInteger,
+    M1_bytes: &[u8],
+  ) -> Result<(SecretInteger, SrpInteger)> {
+    let session = self.server.session(
+      &self.b,
+      &self.B,
+      A,
+      &self.s,
+      &self.username,
+      &self.v,
+      M1_bytes,
+    );
+
+    // A decoy goes through the whole computation before failing like a
+    // wrong password would
+    if self.decoy {
+      return session.and_then(|_| {
+        Err(Error::new(
+          SrpError::BadClientProof,
+          "Client's proof is invalid",
+        ))
+      });
+    }
+
+    session
+  }
+}
+
+/// Server's public ephemeral value, with the id it is stored under
+pub struct ServerChallenge {
+  pub id: String,
+  pub public: Vec<u8>,
+}
+
+// Bytes of randomness in a challenge id
+const CHALLENGE_ID_BYTES: usize = 16;
+
+// Secret ephemeral kept by a ChallengeStore
+pub(crate) struct Challenge {
+  pub(crate) b: SecretInteger,
+  pub(crate) B: SrpInteger,
+  // SHA-256 of the verifier B was computed from, as in sealed tokens
+  verifier: [u8; 32],
+  issued: Instant,
+}
+
+impl Challenge {
+  // Fail unless `v` is the verifier the challenge was issued for, since any
+  // other one could only ever fail as a bad client proof
+  pub(crate) fn check_verifier(&self, v: &SrpInteger) -> Result<()> {
+    if !bool::from(self.verifier.ct_eq(&verifier_fingerprint(v))) {
+      return Err(Error::new(
+        SrpError::ChallengeMismatch,
+        "Verifier does not match the challenge",
+      ));
+    }
+    Ok(())
+  }
+}
+
+/// In-process store of server ephemerals. Each one expires after a TTL and
+/// is consumed by the first derive_session attempt, successful or not.
+pub struct ChallengeStore {
+  pub(crate) server: Server,
+  ttl: Duration,
+  challenges: Mutex<LruCache<String, Challenge>>,
+}
+
+impl ChallengeStore {
+  /// One minute to answer a challenge
+  pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+  /// At most 10000 outstanding challenges
+  pub const DEFAULT_CAPACITY: usize = 10_000;
+
+  /// Create a store issuing ephemerals with the server's group and hash
+  /// function. Once `capacity` challenges are outstanding, the least
+  /// recently issued ones are evicted first.
+  pub fn new(server: &Server, ttl: Duration, capacity: usize) -> Result<Self> {
+    let capacity = NonZeroUsize::new(capacity).ok_or_else(|| {
+      Error::new(
+        SrpError::InvalidOption,
+        "Challenge store capacity must be at least 1",
+      )
+    })?;
+
+    Ok(ChallengeStore {
+      server: server.clone(),
+      ttl,
+      challenges: Mutex::new(LruCache::new(capacity)),
+    })
+  }
+
+  /// Number of challenges issued and not yet consumed or evicted
+  pub fn len(&self) -> usize {
+    self.challenges().len()
+  }
+
+  /// Whether no challenge is outstanding
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Generate a server ephemeral and keep its secret half in the store
+  pub fn issue(&self, verifier: &[u8]) -> ServerChallenge {
+    let prime_bytes = self.server.group.prime_bytes();
+    let (id, public) = self.put(&SrpInteger::from_bytes(verifier), |B| {
+      B.to_padded_bytes(prime_bytes)
+    });
+    ServerChallenge { id, public }
+  }
+
+  /// Derive the session key and proof for an issued challenge, with the
+  /// verifier it was issued for. The challenge is consumed whatever the
+  /// outcome.
+  pub fn derive_session(
+    &self,
+    id: &str,
+    client_public_ephemeral: &[u8],
+    salt: &[u8],
+    username: &str,
+    verifier: &[u8],
+    client_session_proof: &[u8],
+  ) -> Result<ServerSession> {
+    let challenge = self.take(id)?;
+    let v = SrpInteger::from_bytes(verifier);
+    challenge.check_verifier(&v)?;
+    let (K, M2) = self.server.session(
+      &challenge.b,
+      &challenge.B,
+      &SrpInteger::from_bytes(client_public_ephemeral),
+      &SrpInteger::from_bytes(salt),
+      username,
+      &v,
+      client_session_proof,
+    )?;
+    Ok(self.server.encode_session(&K, &M2))
+  }
+}
+
+impl ChallengeStore {
+  // Store a new ephemeral for a verifier, returning its id and B as
+  // encoded by `public`
+  pub(crate) fn put<T>(
+    &self,
+    v: &SrpInteger,
+    public: impl FnOnce(&SrpInteger) -> T,
+  ) -> (String, T) {
+    let (b, B) = self.server.ephemeral(v);
+    let public = public(&B);
+
+    let mut id = [0u8; CHALLENGE_ID_BYTES];
+    self.server.rng.fill(&mut id);
+    let id = hex::encode(id);
+
+    self.challenges().put(
+      id.clone(),
+      Challenge {
+        b,
+        B,
+        verifier: verifier_fingerprint(v),
+        issued: Instant::now(),
+      },
+    );
+
+    (id, public)
+  }
+
+  // Remove a challenge, failing if it is unknown or expired
+  pub(crate) fn take(&self, id: &str) -> Result<Challenge> {
+    let challenge = self.challenges().pop(id).ok_or_else(|| {
+      Error::new(
+        SrpError::UnknownChallenge,
+        "Challenge is unknown or has already been used",
+      )
+    })?;
+
+    if challenge.issued.elapsed() >= self.ttl {
+      return Err(Error::new(
+        SrpError::ChallengeExpired,
+        "Challenge has expired",
+      ));
+    }
+
+    Ok(challenge)
+  }
+
+  fn challenges(&self) -> MutexGuard<'_, LruCache<String, Challenge>> {
+    self
+      .challenges
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
 }
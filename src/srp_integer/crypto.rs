@@ -0,0 +1,237 @@
+// Pure-Rust backend, through crypto-bigint. Its integers have a fixed
+// precision, so this keeps a sign next to a boxed magnitude and widens
+// operands to a common precision whenever two of them meet.
+use super::Backend;
+use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
+use crypto_bigint::{BoxedUint, Limb, NonZero, Odd};
+use std::cmp::Ordering;
+use zeroize::{Zeroize, Zeroizing};
+
+// Only a backend of its own under this feature; num-bigint uses just Signed
+#[cfg(any(test, feature = "backend-crypto-bigint"))]
+pub type SrpInteger = super::Integer<Signed>;
+
+pub struct Signed {
+  negative: bool,
+  magnitude: BoxedUint,
+}
+
+// Whole limbs holding at least `bits`, and at least one of them
+fn precision(bits: u32) -> u32 {
+  bits.max(1).next_multiple_of(Limb::BITS)
+}
+
+// Both magnitudes at one precision, with room for `extra` more bits
+fn aligned(a: &BoxedUint, b: &BoxedUint, extra: u32) -> (BoxedUint, BoxedUint) {
+  let bits = precision(a.bits_precision().max(b.bits_precision()) + extra);
+  (a.widen(bits), b.widen(bits))
+}
+
+fn compare(a: &BoxedUint, b: &BoxedUint) -> Ordering {
+  let (a, b) = aligned(a, b, 0);
+  a.cmp(&b)
+}
+
+// a mod m, at the precision of m
+fn remainder(a: &BoxedUint, m: &BoxedUint) -> BoxedUint {
+  let (a, m_) = aligned(a, m, 0);
+  let m_ = NonZero::new(m_).expect("Division by zero");
+  a.rem(&m_).shorten(m.bits_precision())
+}
+
+impl Signed {
+  // Odd moduli other than 1, which Montgomery form supports
+  fn montgomery_modulus(modulus: &Self) -> Option<Odd<BoxedUint>> {
+    let m = &modulus.magnitude;
+    if bool::from(m.is_one()) {
+      return None;
+    }
+    m.to_odd().into()
+  }
+
+  fn new(negative: bool, magnitude: BoxedUint) -> Self {
+    // Zero is never negative, so that equal values compare equal
+    let negative = negative && !bool::from(magnitude.is_zero());
+    Self {
+      negative,
+      magnitude,
+    }
+  }
+
+  // |a| + |b| when the signs agree, |a| - |b| otherwise
+  fn combine(&self, other: &Self, negate_other: bool) -> Self {
+    let other_negative = other.negative != negate_other;
+    let (a, b) = aligned(&self.magnitude, &other.magnitude, 1);
+
+    if self.negative == other_negative {
+      return Self::new(self.negative, a.wrapping_add(&b));
+    }
+
+    match a.cmp(&b) {
+      Ordering::Less => Self::new(other_negative, b.wrapping_sub(&a)),
+      _ => Self::new(self.negative, a.wrapping_sub(&b)),
+    }
+  }
+}
+
+impl Backend for Signed {
+  fn from_bytes_be(bytes: &[u8]) -> Self {
+    let bits = precision(bytes.len() as u32 * 8);
+    let magnitude = BoxedUint::from_be_slice(bytes, bits).expect("Precision fits the bytes");
+    Self::new(false, magnitude)
+  }
+
+  fn from_hex_digits(hex: &str) -> Option<Self> {
+    let padded = if hex.len() % 2 == 1 {
+      Zeroizing::new(format!("0{}", hex))
+    } else {
+      Zeroizing::new(hex.to_string())
+    };
+    let bytes = Zeroizing::new(hex::decode(padded.as_str()).ok()?);
+    Some(Self::from_bytes_be(&bytes))
+  }
+
+  fn to_hex(&self) -> String {
+    let hex = hex::encode(self.magnitude.to_be_bytes());
+    let digits = match hex.trim_start_matches('0') {
+      "" => "0",
+      digits => digits,
+    };
+
+    if self.negative {
+      format!("-{}", digits)
+    } else {
+      digits.to_string()
+    }
+  }
+
+  fn mod_pow(&self, exp: &Self, modulus: &Self) -> Self {
+    let Some(m) = Self::montgomery_modulus(modulus) else {
+      return self.mod_pow_vartime(exp, modulus);
+    };
+    assert!(!exp.negative, "Negative exponent");
+
+    // pow() runs over every bit of the exponent's precision, leading zeros
+    // included. That precision comes from the byte length the exponent was
+    // built from and the precisions of the operands that produced it, never
+    // from its value, so there is no need to pad it further.
+    let base = Zeroizing::new(self.modulo(modulus));
+
+    let params = BoxedMontyParams::new(m);
+    let power = BoxedMontyForm::new(base.magnitude.clone(), params).pow(&exp.magnitude);
+    Self::new(false, power.retrieve())
+  }
+
+  fn mod_pow_vartime(&self, exp: &Self, modulus: &Self) -> Self {
+    assert!(!exp.negative, "Negative exponent");
+    let m = &modulus.magnitude;
+
+    // Everything is 0 mod 1
+    if bool::from(m.is_one()) {
+      return Self::new(false, BoxedUint::zero());
+    }
+    let base = self.modulo(modulus).magnitude;
+
+    if let Some(m) = Option::<Odd<BoxedUint>>::from(m.to_odd()) {
+      let params = BoxedMontyParams::new_vartime(m);
+      let power = BoxedMontyForm::new(base, params)
+        .pow_bounded_exp(&exp.magnitude, exp.magnitude.bits_vartime());
+      return Self::new(false, power.retrieve());
+    }
+
+    // Plain square-and-multiply for even moduli, which SRP never uses
+    let mut result = remainder(&BoxedUint::one(), m);
+    for i in (0..exp.magnitude.bits_vartime()).rev() {
+      result = remainder(&result.square(), m);
+      if exp.magnitude.bit_vartime(i) {
+        result = remainder(&result.mul(&base), m);
+      }
+    }
+    Self::new(false, result)
+  }
+
+  fn multiply(&self, other: &Self) -> Self {
+    Self::new(
+      self.negative != other.negative,
+      self.magnitude.mul(&other.magnitude),
+    )
+  }
+
+  fn add(&self, other: &Self) -> Self {
+    self.combine(other, false)
+  }
+
+  fn subtract(&self, other: &Self) -> Self {
+    self.combine(other, true)
+  }
+
+  fn modulo(&self, modulus: &Self) -> Self {
+    let m = &modulus.magnitude;
+    let rem = remainder(&self.magnitude, m);
+
+    // -a mod m = m - (a mod m)
+    if self.negative && !bool::from(rem.is_zero()) {
+      return Self::new(false, m.wrapping_sub(&rem));
+    }
+
+    Self::new(false, rem)
+  }
+
+  fn xor(&self, other: &Self) -> Self {
+    // SRP only ever XORs hash outputs
+    assert!(
+      !self.negative && !other.negative,
+      "XOR of a negative integer"
+    );
+    let (a, b) = aligned(&self.magnitude, &other.magnitude, 0);
+    Self::new(false, a.bitxor(&b))
+  }
+
+  fn bits(&self) -> u32 {
+    self.magnitude.bits()
+  }
+
+  fn is_odd(&self) -> bool {
+    self.magnitude.bit(0).into()
+  }
+
+  fn is_zero(&self) -> bool {
+    self.magnitude.is_zero().into()
+  }
+
+  fn shift_right(&self, bits: u32) -> Self {
+    if !self.negative {
+      return Self::new(false, self.magnitude.wrapping_shr(bits));
+    }
+
+    // Round towards negative infinity: -a >> n = -(((a - 1) >> n) + 1)
+    let one = BoxedUint::one_with_precision(self.magnitude.bits_precision());
+    let shifted = self.magnitude.wrapping_sub(&one).wrapping_shr(bits);
+    let (shifted, one) = aligned(&shifted, &one, 1);
+    Self::new(true, shifted.wrapping_add(&one))
+  }
+
+  fn equals(&self, other: &Self) -> bool {
+    self.negative == other.negative && compare(&self.magnitude, &other.magnitude).is_eq()
+  }
+
+  fn is_less_than(&self, other: &Self) -> bool {
+    match (self.negative, other.negative) {
+      (false, true) => false,
+      (true, false) => true,
+      (false, false) => compare(&self.magnitude, &other.magnitude).is_lt(),
+      (true, true) => compare(&self.magnitude, &other.magnitude).is_gt(),
+    }
+  }
+
+  fn wipe(&mut self) {
+    self.zeroize();
+  }
+}
+
+impl Zeroize for Signed {
+  fn zeroize(&mut self) {
+    self.magnitude.zeroize();
+    self.negative = false;
+  }
+}
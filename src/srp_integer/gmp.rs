@@ -0,0 +1,101 @@
+// GMP backend, through rug
+use super::Backend;
+use rug::{Complete, Integer};
+use zeroize::Zeroize;
+
+pub type SrpInteger = super::Integer<Integer>;
+
+impl Backend for Integer {
+  fn from_bytes_be(bytes: &[u8]) -> Self {
+    Integer::from_digits(bytes, rug::integer::Order::Msf)
+  }
+
+  fn from_hex_digits(hex: &str) -> Option<Self> {
+    Integer::parse_radix(hex, 16)
+      .ok()
+      .map(|value| value.complete())
+  }
+
+  fn to_hex(&self) -> String {
+    self.to_string_radix(16)
+  }
+
+  fn mod_pow(&self, exp: &Self, modulus: &Self) -> Self {
+    // mpz_powm_sec takes the same time for exponents of the same number of
+    // limbs, but needs an odd modulus and a positive exponent
+    if modulus.is_odd() && *exp > 0 {
+      return self.clone().secure_pow_mod(exp, modulus);
+    }
+
+    self.mod_pow_vartime(exp, modulus)
+  }
+
+  fn mod_pow_vartime(&self, exp: &Self, modulus: &Self) -> Self {
+    self
+      .clone()
+      .pow_mod(exp, modulus)
+      .expect("Modular exponentiation failed")
+  }
+
+  fn multiply(&self, other: &Self) -> Self {
+    (self * other).complete()
+  }
+
+  fn add(&self, other: &Self) -> Self {
+    (self + other).complete()
+  }
+
+  fn subtract(&self, other: &Self) -> Self {
+    (self - other).complete()
+  }
+
+  fn modulo(&self, modulus: &Self) -> Self {
+    let mut result = self.clone();
+    result %= modulus;
+
+    // Ensure result is positive
+    if result < 0 {
+      result += modulus;
+    }
+
+    result
+  }
+
+  fn xor(&self, other: &Self) -> Self {
+    (self ^ other).complete()
+  }
+
+  fn bits(&self) -> u32 {
+    self.significant_bits()
+  }
+
+  fn is_odd(&self) -> bool {
+    Integer::is_odd(self)
+  }
+
+  fn is_zero(&self) -> bool {
+    Integer::is_zero(self)
+  }
+
+  fn shift_right(&self, bits: u32) -> Self {
+    (self >> bits).complete()
+  }
+
+  fn equals(&self, other: &Self) -> bool {
+    self == other
+  }
+
+  fn is_less_than(&self, other: &Self) -> bool {
+    self < other
+  }
+
+  fn wipe(&mut self) {
+    // Wipe every allocated limb, not only the ones currently in use
+    unsafe {
+      let raw = self.as_raw_mut();
+      let limbs = std::slice::from_raw_parts_mut((*raw).d.as_ptr(), (*raw).alloc as usize);
+      limbs.zeroize();
+      (*raw).size = 0;
+    }
+  }
+}
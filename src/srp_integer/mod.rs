@@ -0,0 +1,387 @@
+// Big integers behind SRP. The arithmetic comes from one of three backends,
+// picked by cargo feature: crypto-bigint, then num-bigint, then GMP (through
+// rug). GMP is unavailable on macOS, MSVC and WebAssembly, which fall back to
+// num-bigint. Tests build every available backend to check they agree.
+use crate::rng::RngSource;
+use std::fmt;
+use std::ops::Deref;
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg(all(
+  feature = "backend-gmp",
+  not(any(target_os = "macos", target_env = "msvc", target_arch = "wasm32")),
+  any(
+    test,
+    not(any(feature = "backend-num-bigint", feature = "backend-crypto-bigint"))
+  )
+))]
+pub(crate) mod gmp;
+
+#[cfg(any(
+  test,
+  all(
+    not(feature = "backend-crypto-bigint"),
+    any(
+      feature = "backend-num-bigint",
+      target_os = "macos",
+      target_env = "msvc",
+      target_arch = "wasm32"
+    )
+  )
+))]
+pub(crate) mod num;
+
+// Also lends its constant-time exponentiation to num-bigint
+#[cfg(any(
+  test,
+  feature = "backend-crypto-bigint",
+  feature = "backend-num-bigint",
+  target_os = "macos",
+  target_env = "msvc",
+  target_arch = "wasm32"
+))]
+pub(crate) mod crypto;
+
+#[cfg(feature = "backend-crypto-bigint")]
+pub use crypto::SrpInteger;
+
+#[cfg(all(
+  not(feature = "backend-crypto-bigint"),
+  any(
+    feature = "backend-num-bigint",
+    target_os = "macos",
+    target_env = "msvc",
+    target_arch = "wasm32"
+  )
+))]
+pub use num::SrpInteger;
+
+#[cfg(all(
+  feature = "backend-gmp",
+  not(any(
+    feature = "backend-num-bigint",
+    feature = "backend-crypto-bigint",
+    target_os = "macos",
+    target_env = "msvc",
+    target_arch = "wasm32"
+  ))
+))]
+pub use gmp::SrpInteger;
+
+#[cfg(not(any(
+  feature = "backend-gmp",
+  feature = "backend-num-bigint",
+  feature = "backend-crypto-bigint",
+  target_os = "macos",
+  target_env = "msvc",
+  target_arch = "wasm32"
+)))]
+compile_error!(
+  "enable a big-integer backend: backend-gmp, backend-num-bigint or backend-crypto-bigint"
+);
+
+/// Signed big-integer arithmetic provided by a backend. Values can drop
+/// below zero in the middle of a computation, e.g. B - kg^x.
+pub trait Backend: Sized {
+  /// Unsigned big-endian bytes
+  fn from_bytes_be(bytes: &[u8]) -> Self;
+
+  /// Hex digits only, no sign or prefix
+  fn from_hex_digits(hex: &str) -> Option<Self>;
+
+  /// Lowercase hex without leading zeros, preceded by "-" when negative
+  fn to_hex(&self) -> String;
+
+  /// self^exp mod modulus, in [0, modulus). Takes the same time for every
+  /// exponent of a given size, as exponents are often secret.
+  fn mod_pow(&self, exp: &Self, modulus: &Self) -> Self;
+
+  /// Faster self^exp mod modulus whose timing depends on the exponent, only
+  /// for public exponents
+  fn mod_pow_vartime(&self, exp: &Self, modulus: &Self) -> Self;
+
+  fn multiply(&self, other: &Self) -> Self;
+
+  fn add(&self, other: &Self) -> Self;
+
+  fn subtract(&self, other: &Self) -> Self;
+
+  /// self mod modulus, in [0, modulus)
+  fn modulo(&self, modulus: &Self) -> Self;
+
+  fn xor(&self, other: &Self) -> Self;
+
+  /// Number of significant bits
+  fn bits(&self) -> u32;
+
+  fn is_odd(&self) -> bool;
+
+  fn is_zero(&self) -> bool;
+
+  /// Division by 2^bits, rounded down
+  fn shift_right(&self, bits: u32) -> Self;
+
+  fn equals(&self, other: &Self) -> bool;
+
+  fn is_less_than(&self, other: &Self) -> bool;
+
+  /// Overwrite every limb in memory with zeros
+  fn wipe(&mut self);
+}
+
+// Modular exponentiations run on this thread, so tests can check that two
+// code paths do the same expensive work
+#[cfg(test)]
+thread_local! {
+  pub(crate) static MOD_POWS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(test)]
+fn count_mod_pow() {
+  MOD_POWS.with(|count| count.set(count.get() + 1));
+}
+
+/// Integer of a given backend, with the hex length it pads to
+pub struct Integer<B: Backend> {
+  value: B,
+  hex_length: Option<usize>,
+}
+
+impl<B: Backend> Integer<B> {
+  // Efficiently create from bytes
+  pub fn from_bytes(bytes: &[u8]) -> Self {
+    Self {
+      value: B::from_bytes_be(bytes),
+      hex_length: Some(bytes.len() * 2), // Each byte corresponds to two hex characters
+    }
+  }
+
+  pub fn from_hex(hex: &str) -> Result<Self, String> {
+    // Clean input (wiped on drop, it may hold a secret)
+    let cleaned_hex = Zeroizing::new(hex.trim().replace([' ', '\n'], ""));
+
+    // Digits only: no sign, prefix or separators, which the backends
+    // would each accept differently
+    if cleaned_hex.is_empty() || !cleaned_hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+      return Err(format!("Invalid hex string: {}", hex));
+    }
+
+    match B::from_hex_digits(&cleaned_hex) {
+      Some(value) => Ok(Self {
+        value,
+        hex_length: Some(cleaned_hex.len()),
+      }),
+      None => Err(format!("Invalid hex string: {}", hex)),
+    }
+  }
+
+  pub fn to_hex(&self) -> String {
+    if self.hex_length.is_none() {
+      panic!("This SrpInteger has no specified length");
+    }
+
+    let hex = self.value.to_hex();
+
+    // Pad with leading zeros like the JS version does
+    if let Some(len) = self.hex_length {
+      if hex.len() < len {
+        return "0".repeat(len - hex.len()) + &hex;
+      }
+    }
+
+    hex
+  }
+
+  // Big-endian bytes, left-padded to the specified length
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut hex = Zeroizing::new(self.to_hex());
+    if hex.len() % 2 == 1 {
+      hex.insert(0, '0');
+    }
+    hex::decode(hex.as_str()).expect("Hex from to_hex should always decode")
+  }
+
+  // Big-endian bytes without leading zeros, left-padded to `len` bytes
+  pub fn to_padded_bytes(&self, len: usize) -> Vec<u8> {
+    let bytes = Zeroizing::new(self.to_bytes());
+    let start = bytes
+      .iter()
+      .position(|byte| *byte != 0)
+      .unwrap_or(bytes.len());
+    let significant = &bytes[start..];
+
+    let mut padded = vec![0u8; len.saturating_sub(significant.len())];
+    padded.extend_from_slice(significant);
+    padded
+  }
+
+  // Random integer of the given byte length, drawn from the given source
+  pub fn random_integer(rng: &RngSource, bytes: usize) -> Self {
+    let mut buf = Zeroizing::new(vec![0u8; bytes]);
+    rng.fill(&mut buf);
+    Self::from_bytes(&buf)
+  }
+
+  pub fn equals(&self, other: &Self) -> bool {
+    self.value.equals(&other.value)
+  }
+
+  pub fn is_less_than(&self, other: &Self) -> bool {
+    self.value.is_less_than(&other.value)
+  }
+
+  // Calculate (B - kg^x) ^ (a + ux)
+  pub fn subtract_mult_pow(
+    &self,
+    k: &Self,
+    g: &Self,
+    x: &Self,
+    a: &Self,
+    u: &Self,
+    modulus: &Self,
+  ) -> Self {
+    let gx = Zeroizing::new(g.mod_pow(x, modulus));
+    let kgx = Zeroizing::new(k.multiply(&gx));
+    let B_minus_kgx = Zeroizing::new(self.subtract(&kgx));
+    let ux = Zeroizing::new(u.multiply(x));
+    let a_plus_ux = Zeroizing::new(a.add(&ux));
+    B_minus_kgx.mod_pow(&a_plus_ux, modulus)
+  }
+
+  // Calculate kv + g^b
+  pub fn add_mult_pow(&self, k: &Self, v: &Self, g: &Self, b: &Self, modulus: &Self) -> Self {
+    let gb = g.mod_pow(b, modulus);
+    let kv = k.multiply(v);
+    kv.add(&gb).mod_(modulus)
+  }
+
+  // Constant time with respect to exp, which is often secret (x, a, b, ...)
+  pub fn mod_pow(&self, exp: &Self, modulus: &Self) -> Self {
+    #[cfg(test)]
+    count_mod_pow();
+    Self {
+      value: self.value.mod_pow(&exp.value, &modulus.value),
+      hex_length: modulus.hex_length,
+    }
+  }
+
+  // Variable time, only for public exponents (u, primality checks)
+  pub fn mod_pow_vartime(&self, exp: &Self, modulus: &Self) -> Self {
+    #[cfg(test)]
+    count_mod_pow();
+    Self {
+      value: self.value.mod_pow_vartime(&exp.value, &modulus.value),
+      hex_length: modulus.hex_length,
+    }
+  }
+
+  pub fn multiply(&self, other: &Self) -> Self {
+    Self {
+      value: self.value.multiply(&other.value),
+      hex_length: self.hex_length.or(other.hex_length),
+    }
+  }
+
+  pub fn add(&self, other: &Self) -> Self {
+    Self {
+      value: self.value.add(&other.value),
+      hex_length: self.hex_length.or(other.hex_length),
+    }
+  }
+
+  pub fn subtract(&self, other: &Self) -> Self {
+    Self {
+      value: self.value.subtract(&other.value),
+      hex_length: self.hex_length.or(other.hex_length),
+    }
+  }
+
+  pub fn mod_(&self, modulus: &Self) -> Self {
+    Self {
+      value: self.value.modulo(&modulus.value),
+      hex_length: modulus.hex_length,
+    }
+  }
+
+  pub fn xor(&self, other: &Self) -> Self {
+    Self {
+      value: self.value.xor(&other.value),
+      hex_length: self.hex_length.or(other.hex_length),
+    }
+  }
+
+  // Number of significant bits
+  pub fn bits(&self) -> u32 {
+    self.value.bits()
+  }
+
+  pub fn is_odd(&self) -> bool {
+    self.value.is_odd()
+  }
+
+  pub fn shift_right(&self, bits: u32) -> Self {
+    Self {
+      value: self.value.shift_right(bits),
+      hex_length: None,
+    }
+  }
+
+  // Check if the integer is zero
+  pub fn is_zero(&self) -> bool {
+    self.value.is_zero()
+  }
+
+  // Compute modulo
+  pub fn modulo(&self, modulus: &Self) -> Self {
+    self.mod_(modulus)
+  }
+}
+
+impl<B: Backend> Zeroize for Integer<B> {
+  fn zeroize(&mut self) {
+    self.value.wipe();
+  }
+}
+
+impl<B: Backend> fmt::Debug for Integer<B> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let hex = self.value.to_hex();
+    if hex.len() > 16 {
+      write!(f, "<SrpInteger {}...>", &hex[0..16])
+    } else {
+      write!(f, "<SrpInteger {}>", hex)
+    }
+  }
+}
+
+/// SrpInteger holding secret material, wiped from memory when dropped
+pub struct SecretInteger(SrpInteger);
+
+impl SecretInteger {
+  pub fn new(value: SrpInteger) -> Self {
+    Self(value)
+  }
+
+  #[cfg(any(feature = "napi", feature = "wasm"))]
+  pub fn from_hex(hex: &str) -> Result<Self, String> {
+    SrpInteger::from_hex(hex).map(Self)
+  }
+
+  pub fn random_integer(rng: &RngSource, bytes: usize) -> Self {
+    Self(SrpInteger::random_integer(rng, bytes))
+  }
+}
+
+impl Deref for SecretInteger {
+  type Target = SrpInteger;
+
+  fn deref(&self) -> &SrpInteger {
+    &self.0
+  }
+}
+
+impl Drop for SecretInteger {
+  fn drop(&mut self) {
+    self.0.zeroize();
+  }
+}
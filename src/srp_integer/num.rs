@@ -0,0 +1,101 @@
+// Pure-Rust backend, through num-bigint
+use super::crypto::Signed;
+use super::Backend;
+use num_bigint::{BigInt, BigUint, Sign};
+use zeroize::Zeroizing;
+
+pub type SrpInteger = super::Integer<BigInt>;
+
+impl Backend for BigInt {
+  fn from_bytes_be(bytes: &[u8]) -> Self {
+    BigUint::from_bytes_be(bytes).into()
+  }
+
+  fn from_hex_digits(hex: &str) -> Option<Self> {
+    BigUint::parse_bytes(hex.as_bytes(), 16).map(BigInt::from)
+  }
+
+  fn to_hex(&self) -> String {
+    self.to_str_radix(16)
+  }
+
+  fn mod_pow(&self, exp: &Self, modulus: &Self) -> Self {
+    // num-bigint has no constant-time exponentiation, so borrow crypto-bigint's.
+    // Reducing the base first leaves only non-negative values to convert.
+    // num-bigint drops leading zeros, so the exponent's precision is its
+    // significant length rounded up to whole limbs: it only shrinks when a
+    // secret's top 64 bits are all zero.
+    assert!(exp.sign() != Sign::Minus, "Negative exponent");
+    let mut reduced = self.modulo(modulus);
+    let [base, exp, modulus] = [&reduced, exp, modulus].map(|value| {
+      let bytes = Zeroizing::new(value.magnitude().to_bytes_be());
+      Zeroizing::new(Signed::from_bytes_be(&bytes))
+    });
+    reduced.wipe();
+    let power = Zeroizing::new(Zeroizing::new(base.mod_pow(&exp, &modulus)).to_hex());
+
+    Self::from_hex_digits(&power).expect("Backend hex is valid")
+  }
+
+  fn mod_pow_vartime(&self, exp: &Self, modulus: &Self) -> Self {
+    self.modpow(exp, modulus)
+  }
+
+  fn multiply(&self, other: &Self) -> Self {
+    self * other
+  }
+
+  fn add(&self, other: &Self) -> Self {
+    self + other
+  }
+
+  fn subtract(&self, other: &Self) -> Self {
+    self - other
+  }
+
+  fn modulo(&self, modulus: &Self) -> Self {
+    let mut result = self % modulus;
+
+    if result.sign() == Sign::Minus {
+      result += modulus;
+    }
+
+    result
+  }
+
+  fn xor(&self, other: &Self) -> Self {
+    self ^ other
+  }
+
+  fn bits(&self) -> u32 {
+    BigInt::bits(self) as u32
+  }
+
+  fn is_odd(&self) -> bool {
+    self.bit(0)
+  }
+
+  fn is_zero(&self) -> bool {
+    self.sign() == Sign::NoSign
+  }
+
+  fn shift_right(&self, bits: u32) -> Self {
+    self >> bits
+  }
+
+  fn equals(&self, other: &Self) -> bool {
+    self == other
+  }
+
+  fn is_less_than(&self, other: &Self) -> bool {
+    self < other
+  }
+
+  fn wipe(&mut self) {
+    // num-bigint has no mutable access to its digits, but assign_from_slice
+    // reuses the existing allocation, so this overwrites them with zeros
+    let digits = self.magnitude().iter_u64_digits().len();
+    self.assign_from_slice(Sign::Plus, &vec![0u32; digits * 2]);
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+  }
+}
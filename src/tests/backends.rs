@@ -0,0 +1,249 @@
+// Backends must agree on every SrpInteger operation, down to the padding of
+// the hex they produce, or builds of this crate with different backends
+// would derive different keys and proofs from the same handshake
+use crate::params::Group;
+use crate::rng::RngSource;
+use crate::SrpGroup;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+// Runs the same body on every backend built, with the named hex inputs
+// parsed by each of them, and checks that they all produce the same result
+macro_rules! agree {
+  (@on $backend:ident, $($name:ident = $hex:expr),* => $body:expr) => {{
+    #[allow(unused_imports)]
+    use crate::srp_integer::$backend::SrpInteger;
+    $(let $name = SrpInteger::from_hex($hex).unwrap();)*
+    $body
+  }};
+  ($($name:ident = $hex:expr),* => $body:expr) => {{
+    #[allow(unused_mut)]
+    let mut results = vec![
+      ("num-bigint", agree!(@on num, $($name = $hex),* => $body)),
+      ("crypto-bigint", agree!(@on crypto, $($name = $hex),* => $body)),
+    ];
+    #[cfg(all(
+      feature = "backend-gmp",
+      not(any(target_os = "macos", target_env = "msvc", target_arch = "wasm32"))
+    ))]
+    results.push(("GMP", agree!(@on gmp, $($name = $hex),* => $body)));
+
+    let inputs: Vec<String> = vec![$($hex.to_string()),*];
+    for (backend, result) in &results[1..] {
+      assert_eq!(
+        &results[0].1, result,
+        "{} on {} with {:?}", stringify!($body), backend, inputs
+      );
+    }
+  }};
+}
+
+// Values around limb and byte boundaries, then every builtin prime
+fn edge_cases() -> Vec<String> {
+  let mut values: Vec<String> = [
+    "0",
+    "00",
+    "1",
+    "01",
+    "02",
+    "ff",
+    "0100",
+    "7fffffffffffffff",
+    "8000000000000000",
+    "ffffffffffffffff",
+    "010000000000000000",
+    "00000000ffffffffffffffffffffffffffffffff",
+  ]
+  .into_iter()
+  .map(String::from)
+  .collect();
+
+  values.extend(
+    SrpGroup::ALL
+      .into_iter()
+      .map(|group| hex::encode(Group::from(group).prime())),
+  );
+  values
+}
+
+// Random values of up to `max_bytes`, some of them with leading zeros
+fn random(rng: &mut ChaCha20Rng, count: usize, max_bytes: usize) -> Vec<String> {
+  (0..count)
+    .map(|_| {
+      let mut bytes = vec![0u8; rng.gen_range(1..=max_bytes)];
+      rng.fill(bytes.as_mut_slice());
+      if rng.gen_bool(0.2) {
+        bytes[0] = 0;
+      }
+      hex::encode(bytes)
+    })
+    .collect()
+}
+
+fn inputs(seed: u64, count: usize, max_bytes: usize) -> Vec<String> {
+  let mut values = edge_cases();
+  values.extend(random(
+    &mut ChaCha20Rng::seed_from_u64(seed),
+    count,
+    max_bytes,
+  ));
+  values
+}
+
+fn sha256(parts: &[Vec<u8>]) -> Vec<u8> {
+  let mut hasher = Sha256::new();
+  for part in parts {
+    hasher.update(part);
+  }
+  hasher.finalize().to_vec()
+}
+
+#[test]
+fn parsing() {
+  for hex in &inputs(1, 40, 512) {
+    let spaced = hex
+      .as_bytes()
+      .chunks(8)
+      .map(|chunk| std::str::from_utf8(chunk).unwrap())
+      .collect::<Vec<_>>()
+      .join(" \n");
+    let upper = hex.to_uppercase();
+
+    agree!(x = hex => x.to_hex());
+    agree!(x = hex => x.to_bytes());
+    agree!(x = hex => x.to_padded_bytes(0));
+    agree!(x = hex => x.to_padded_bytes(hex.len() / 2 + 2));
+    agree!(x = hex => x.bits());
+    agree!(x = hex => x.is_odd());
+    agree!(x = hex => x.is_zero());
+    agree!(x = &spaced => x.to_hex());
+    agree!(x = &upper => x.to_hex());
+    agree!(x = &format!("00{}", hex) => x.to_hex());
+    agree!(x = hex => SrpInteger::from_bytes(&x.to_bytes()).to_hex());
+  }
+}
+
+#[test]
+fn invalid_hex() {
+  for hex in ["", " ", "xyz", "0x10", "12g4", "-1", "+1", "1_0"] {
+    agree!(=> SrpInteger::from_hex(hex).map(|x| x.to_hex()).ok());
+  }
+}
+
+#[test]
+fn arithmetic() {
+  let values = inputs(2, 16, 160);
+
+  for x in &values {
+    for y in &values {
+      agree!(x = x, y = y => x.add(&y).to_hex());
+      agree!(x = x, y = y => x.subtract(&y).to_hex());
+      agree!(x = x, y = y => x.multiply(&y).to_hex());
+      agree!(x = x, y = y => x.xor(&y).to_hex());
+      agree!(x = x, y = y => x.equals(&y));
+      agree!(x = x, y = y => x.is_less_than(&y));
+
+      agree!(x = x, y = y => {
+        (!y.is_zero()).then(|| (x.mod_(&y).to_hex(), x.modulo(&y).to_hex()))
+      });
+      agree!(x = x, y = y, m = &values[values.len() - 1] => {
+        x.subtract(&y).mod_(&m).to_hex()
+      });
+    }
+
+    for bits in [0, 1, 7, 8, 63, 64, 65, 200] {
+      agree!(x = x, zero = "0" => x.shift_right(bits).add(&zero).to_hex());
+      agree!(x = x, zero = "0", one = "1" => {
+        zero.subtract(&x).subtract(&one).shift_right(bits).add(&zero).to_hex()
+      });
+    }
+  }
+}
+
+#[test]
+fn modular_exponentiation() {
+  let mut rng = ChaCha20Rng::seed_from_u64(3);
+  let mut bases = edge_cases();
+  bases.extend(random(&mut rng, 12, 256));
+  let mut exponents: Vec<String> = ["0", "1", "02", "ff"].map(String::from).into();
+  exponents.extend(random(&mut rng, 8, 40));
+  let mut moduli: Vec<String> = ["1", "02", "ff", "0100"].map(String::from).into();
+  moduli.extend(
+    random(&mut rng, 4, 64)
+      .into_iter()
+      .filter(|m| !m.trim_start_matches('0').is_empty()),
+  );
+  moduli.extend(
+    [SrpGroup::RFC5054_1024, SrpGroup::RFC5054_2048]
+      .map(|group| hex::encode(Group::from(group).prime())),
+  );
+
+  for m in &moduli {
+    for base in &bases {
+      for exp in &exponents {
+        agree!(x = base, e = exp, m = m => {
+          let power = x.mod_pow(&e, &m).to_hex();
+          assert_eq!(power, x.mod_pow_vartime(&e, &m).to_hex());
+          power
+        });
+      }
+    }
+
+    let args = random(&mut rng, 6, 48);
+    agree!(
+      B = &args[0], k = &args[1], g = &args[2], x = &args[3], a = &args[4], u = &args[5], m = m
+      => B.subtract_mult_pow(&k, &g, &x, &a, &u, &m).to_hex()
+    );
+    agree!(
+      k = &args[0], v = &args[1], g = &args[2], b = &args[3], m = m
+      => g.add_mult_pow(&k, &v, &g, &b, &m).to_hex()
+    );
+  }
+}
+
+#[test]
+fn random_integers() {
+  for bytes in [1, 16, 32, 256] {
+    agree!(=> {
+      let rng = RngSource::new(ChaCha20Rng::seed_from_u64(bytes as u64));
+      SrpInteger::random_integer(&rng, bytes).to_hex()
+    });
+  }
+}
+
+// Full SRP-6a exchanges, client and server side, over every builtin group
+#[test]
+fn handshakes() {
+  let mut rng = ChaCha20Rng::seed_from_u64(4);
+
+  for group in SrpGroup::ALL {
+    let group = Group::from(group);
+    let (N_hex, g_hex) = (hex::encode(group.prime()), hex::encode(group.generator()));
+    let secrets = random(&mut rng, 3, 32);
+
+    agree!(N = &N_hex, g = &g_hex, a = &secrets[0], b = &secrets[1], x = &secrets[2] => {
+      // k = H(N, g)
+      let k = SrpInteger::from_bytes(&sha256(&[N.to_bytes(), g.to_bytes()]));
+
+      // v = g^x, A = g^a and B = kv + g^b
+      let v = g.mod_pow(&x, &N);
+      let A = g.mod_pow(&a, &N);
+      let B = g.add_mult_pow(&k, &v, &g, &b, &N);
+
+      // u = H(A, B)
+      let u = SrpInteger::from_bytes(&sha256(&[A.to_bytes(), B.to_bytes()]));
+
+      // S = (B - kg^x) ^ (a + ux) on the client, (Av^u) ^ b on the server
+      let S_client = B.subtract_mult_pow(&k, &g, &x, &a, &u, &N);
+      let S_server = A.multiply(&v.mod_pow_vartime(&u, &N)).mod_pow(&b, &N);
+      assert!(S_client.equals(&S_server));
+
+      [v, A, B, u, S_client]
+        .map(|value| value.to_hex())
+        .into_iter()
+        .chain([hex::encode(sha256(&[S_server.to_bytes()]))])
+        .collect::<Vec<_>>()
+    });
+  }
+}
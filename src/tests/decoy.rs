@@ -0,0 +1,110 @@
+// Decoy handshakes for unknown users must cost and fail like real ones,
+// sealed or not, or they tell which usernames exist
+use crate::srp_integer::{SrpInteger, MOD_POWS};
+use crate::{Client, Server, ServerHandshake, SrpError, SrpGroup, SrpHash};
+
+const KEY: [u8; 32] = [7; 32];
+
+// Modular exponentiations done by `f` on this thread
+fn mod_pows<T>(f: impl FnOnce() -> T) -> (T, usize) {
+  let before = MOD_POWS.with(|count| count.get());
+  let result = f();
+  (result, MOD_POWS.with(|count| count.get()) - before)
+}
+
+fn pair() -> (Client, Server) {
+  (
+    Client::new(SrpGroup::RFC5054_1024, SrpHash::SHA256),
+    Server::new(SrpGroup::RFC5054_1024, SrpHash::SHA256),
+  )
+}
+
+#[test]
+fn challenge_costs_the_same() {
+  let (client, server) = pair();
+  let salt = client.generate_salt();
+  let verifier = client.derive_verifier(&client.derive_private_key(&salt, "alice", "password"));
+
+  let (real, real_pows) = mod_pows(|| server.start_handshake("alice", &salt, &verifier));
+  let (decoy, decoy_pows) = mod_pows(|| server.fake_user_challenge("nobody", &KEY).unwrap());
+  assert_eq!(decoy_pows, real_pows);
+  assert_eq!(decoy.salt().len(), real.salt().len());
+  assert_eq!(decoy.public().len(), real.public().len());
+
+  // finish() too, down to the failed proof
+  let mut attempt = client.start_handshake("alice", "wrong password", None);
+  let A = attempt.public();
+  let proof = attempt.compute_proof(&real.salt(), &real.public()).unwrap();
+  let (real, real_pows) = mod_pows(|| real.finish(&A, &proof));
+  let (decoy, decoy_pows) = mod_pows(|| decoy.finish(&A, &proof));
+  assert_eq!(decoy_pows, real_pows);
+  assert_eq!(real.err().unwrap().code(), SrpError::BadClientProof);
+  assert_eq!(decoy.err().unwrap().code(), SrpError::BadClientProof);
+}
+
+#[test]
+fn decoy_verifier_is_stable_and_in_range() {
+  let (_, server) = pair();
+  let (N, _) = server.group.constants();
+
+  let v = server.decoy_verifier(&KEY, "nobody");
+  assert!(v.equals(&server.decoy_verifier(&KEY, "nobody")));
+  assert!(!v.equals(&server.decoy_verifier(&KEY, "somebody")));
+  assert!(!v.equals(&server.decoy_verifier(&[8; 32], "nobody")));
+  assert!(v.is_less_than(N) && !v.is_zero());
+}
+
+#[test]
+fn decoy_rejects_a_correct_proof() {
+  // A decoy built over a real verifier still fails, so failure never
+  // depends on the fake verifier
+  let (client, server) = pair();
+  let salt = client.generate_salt();
+  let verifier = client.derive_verifier(&client.derive_private_key(&salt, "alice", "password"));
+  let decoy = server.handshake(
+    "alice".to_string(),
+    SrpInteger::from_bytes(&salt),
+    SrpInteger::from_bytes(&verifier),
+    true,
+  );
+
+  let mut attempt = client.start_handshake("alice", "password", None);
+  let A = attempt.public();
+  let proof = attempt.compute_proof(&salt, &decoy.public()).unwrap();
+  let error = decoy.finish(&A, &proof).err().unwrap();
+  assert_eq!(error.code(), SrpError::BadClientProof);
+}
+
+#[test]
+fn sealed_decoy_resumes_without_a_verifier() {
+  let (client, server) = pair();
+  let salt = client.generate_salt();
+  let verifier = client.derive_verifier(&client.derive_private_key(&salt, "alice", "password"));
+  let ttl = ServerHandshake::DEFAULT_TOKEN_TTL;
+
+  let real = server.start_handshake("alice", &salt, &verifier);
+  let real = real.seal(&KEY, ttl).unwrap();
+  let decoy = server.fake_user_challenge("nobody", &[8; 32]).unwrap();
+  let (decoy_salt, decoy_public) = (decoy.salt(), decoy.public());
+  let decoy = decoy.seal(&KEY, ttl).unwrap();
+  assert_eq!(decoy.len(), real.len() - "alice".len() + "nobody".len());
+
+  for lookup in [None, Some(verifier.as_slice())] {
+    let resumed = server.resume_handshake(&decoy, &KEY, lookup).unwrap();
+    assert_eq!(resumed.salt(), decoy_salt);
+    assert_eq!(resumed.public(), decoy_public);
+
+    let mut attempt = client.start_handshake("nobody", "password", None);
+    let A = attempt.public();
+    let proof = attempt.compute_proof(&decoy_salt, &decoy_public).unwrap();
+    let error = resumed.finish(&A, &proof).err().unwrap();
+    assert_eq!(error.code(), SrpError::BadClientProof);
+  }
+
+  // A real token still needs its verifier
+  let error = server.resume_handshake(&real, &KEY, None).err().unwrap();
+  assert_eq!(error.code(), SrpError::TokenMismatch);
+  assert!(server
+    .resume_handshake(&real, &KEY, Some(&verifier))
+    .is_ok());
+}
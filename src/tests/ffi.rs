@@ -0,0 +1,180 @@
+// The C ABI end to end, through the exported functions only
+use std::ffi::CString;
+use std::ptr;
+
+use crate::ffi::*;
+
+// Size of an output buffer large enough for any value written here
+const MAX: usize = 1024;
+
+// Call an exported function that fills a caller-provided buffer
+fn output(call: impl FnOnce(*mut u8, *mut usize) -> SrpStatus) -> Vec<u8> {
+  let mut out = vec![0; MAX];
+  let mut len = out.len();
+  assert_eq!(call(out.as_mut_ptr(), &mut len), SrpStatus::Ok);
+  out.truncate(len);
+  out
+}
+
+#[test]
+fn header_is_up_to_date() {
+  let generated = include_str!(concat!(env!("OUT_DIR"), "/srp.h"));
+  assert!(
+    generated == include_str!("../../include/srp.h"),
+    "include/srp.h is stale, copy the one generated in OUT_DIR over it"
+  );
+}
+
+#[test]
+fn handshake() {
+  let username = CString::new("alice").unwrap();
+  let password = CString::new("password123").unwrap();
+  unsafe {
+    let mut client = ptr::null_mut();
+    let mut server = ptr::null_mut();
+    assert_eq!(srp_client_new(0, 0, &mut client), SrpStatus::Ok);
+    assert_eq!(srp_server_new(0, 0, &mut server), SrpStatus::Ok);
+
+    let salt = output(|out, len| srp_client_generate_salt(client, out, len));
+    let private_key = output(|out, len| {
+      srp_client_derive_private_key(
+        client,
+        salt.as_ptr(),
+        salt.len(),
+        username.as_ptr(),
+        password.as_ptr(),
+        out,
+        len,
+      )
+    });
+    let verifier = output(|out, len| {
+      srp_client_derive_verifier(client, private_key.as_ptr(), private_key.len(), out, len)
+    });
+
+    let mut client_handshake = ptr::null_mut();
+    let mut server_handshake = ptr::null_mut();
+    let status = srp_client_start_handshake(
+      client,
+      username.as_ptr(),
+      password.as_ptr(),
+      &mut client_handshake,
+    );
+    assert_eq!(status, SrpStatus::Ok);
+    let status = srp_server_start_handshake(
+      server,
+      username.as_ptr(),
+      salt.as_ptr(),
+      salt.len(),
+      verifier.as_ptr(),
+      verifier.len(),
+      &mut server_handshake,
+    );
+    assert_eq!(status, SrpStatus::Ok);
+
+    let client_public = output(|out, len| srp_client_handshake_public(client_handshake, out, len));
+    let server_public = output(|out, len| srp_server_handshake_public(server_handshake, out, len));
+    let server_salt = output(|out, len| srp_server_handshake_salt(server_handshake, out, len));
+    assert_eq!(server_salt, salt);
+
+    let client_proof = output(|out, len| {
+      srp_client_handshake_compute_proof(
+        client_handshake,
+        server_salt.as_ptr(),
+        server_salt.len(),
+        server_public.as_ptr(),
+        server_public.len(),
+        out,
+        len,
+      )
+    });
+    let (mut server_key, mut server_proof) = ([0; MAX], [0; MAX]);
+    let (mut key_len, mut proof_len) = (MAX, MAX);
+    let status = srp_server_handshake_finish(
+      server_handshake,
+      client_public.as_ptr(),
+      client_public.len(),
+      client_proof.as_ptr(),
+      client_proof.len(),
+      server_key.as_mut_ptr(),
+      &mut key_len,
+      server_proof.as_mut_ptr(),
+      &mut proof_len,
+    );
+    assert_eq!(status, SrpStatus::Ok);
+    assert_eq!((key_len, proof_len), (32, 32));
+
+    let client_key = output(|out, len| {
+      srp_client_handshake_finish(client_handshake, server_proof.as_ptr(), proof_len, out, len)
+    });
+    assert_eq!(client_key, &server_key[..key_len]);
+
+    // Both sides are done with their sessions
+    let status = srp_client_handshake_finish(
+      client_handshake,
+      server_proof.as_ptr(),
+      proof_len,
+      server_key.as_mut_ptr(),
+      &mut key_len,
+    );
+    assert_eq!(status, SrpStatus::HandshakeFinished);
+    let status =
+      srp_server_handshake_public(server_handshake, server_key.as_mut_ptr(), &mut key_len);
+    assert_eq!(status, SrpStatus::HandshakeFinished);
+
+    srp_client_handshake_free(client_handshake);
+    srp_server_handshake_free(server_handshake);
+    srp_client_free(client);
+    srp_server_free(server);
+  }
+}
+
+#[test]
+fn rejects_null_pointers_and_short_buffers() {
+  let username = CString::new("alice").unwrap();
+  unsafe {
+    let mut client = ptr::null_mut();
+    let mut out = [0; MAX];
+    let mut len = out.len();
+    assert_eq!(
+      srp_client_new(0, 0, ptr::null_mut()),
+      SrpStatus::NullPointer
+    );
+    assert_eq!(
+      srp_client_generate_salt(ptr::null_mut(), out.as_mut_ptr(), &mut len),
+      SrpStatus::NullPointer
+    );
+    assert_eq!(srp_client_new(0, 0, &mut client), SrpStatus::Ok);
+    assert_eq!(
+      srp_client_generate_salt(client, out.as_mut_ptr(), ptr::null_mut()),
+      SrpStatus::NullPointer
+    );
+    let status = srp_client_derive_private_key(
+      client,
+      out.as_ptr(),
+      32,
+      username.as_ptr(),
+      ptr::null(),
+      out.as_mut_ptr(),
+      &mut len,
+    );
+    assert_eq!(status, SrpStatus::NullPointer);
+
+    // A short buffer is left alone and told the length it needs
+    len = 4;
+    assert_eq!(
+      srp_client_generate_salt(client, out.as_mut_ptr(), &mut len),
+      SrpStatus::BufferTooSmall
+    );
+    assert_eq!((len, out), (32, [0; MAX]));
+
+    // So is a null buffer, which is how callers ask for the length
+    len = 0;
+    let status = srp_client_derive_verifier(client, out.as_ptr(), 32, ptr::null_mut(), &mut len);
+    assert_eq!((status, len), (SrpStatus::BufferTooSmall, 256));
+
+    // Freeing null is a no-op
+    srp_client_handshake_free(ptr::null_mut());
+    srp_client_free(client);
+    srp_client_free(ptr::null_mut());
+  }
+}
@@ -0,0 +1,65 @@
+// The client handshake state machine every binding wraps
+use crate::{ChallengeStore, Client, Server, SrpError, SrpGroup, SrpHash};
+
+#[test]
+fn client_handshake_runs_once() {
+  let client = Client::new(SrpGroup::RFC5054_1024, SrpHash::SHA256);
+  let server = Server::new(SrpGroup::RFC5054_1024, SrpHash::SHA256);
+  let salt = client.generate_salt();
+  let verifier = client.derive_verifier(&client.derive_private_key(&salt, "alice", "password"));
+  let challenge = server.start_handshake("alice", &salt, &verifier);
+  let B = challenge.public();
+
+  // Finishing first is out of order but leaves the handshake usable
+  let mut handshake = client.start_handshake("alice", "password", None);
+  let error = handshake.finish(&[0; 32]).err().unwrap();
+  assert_eq!(error.code(), SrpError::HandshakeOutOfOrder);
+
+  let proof = handshake.compute_proof(&salt, &B).unwrap();
+  let error = handshake.compute_proof(&salt, &B).err().unwrap();
+  assert_eq!(error.code(), SrpError::HandshakeOutOfOrder);
+
+  // A wrong M2 finishes the handshake all the same
+  let session = challenge.finish(&handshake.public(), &proof).unwrap();
+  let error = handshake.finish(&[0; 32]).err().unwrap();
+  assert_eq!(error.code(), SrpError::BadServerProof);
+  let error = handshake.finish(&session.proof).err().unwrap();
+  assert_eq!(error.code(), SrpError::HandshakeFinished);
+
+  // So does a failed proof, dropping the secret ephemeral
+  let mut handshake = client.start_handshake("alice", "password", None);
+  let error = handshake.compute_proof(&salt, &[0]).err().unwrap();
+  assert_eq!(error.code(), SrpError::InvalidPublicEphemeral);
+  let error = handshake.compute_proof(&salt, &B).err().unwrap();
+  assert_eq!(error.code(), SrpError::HandshakeOutOfOrder);
+}
+
+#[test]
+fn challenge_is_bound_to_its_verifier() {
+  let client = Client::new(SrpGroup::RFC5054_1024, SrpHash::SHA256);
+  let server = Server::new(SrpGroup::RFC5054_1024, SrpHash::SHA256);
+  let store = ChallengeStore::new(&server, ChallengeStore::DEFAULT_TTL, 8).unwrap();
+  let salt = client.generate_salt();
+  let alice = client.derive_private_key(&salt, "alice", "password");
+  let bob = client.derive_private_key(&salt, "bob", "password");
+  let ephemeral = client.generate_ephemeral();
+
+  // Answering with another verifier fails before any proof is checked
+  let challenge = store.issue(&client.derive_verifier(&alice));
+  let session = client
+    .derive_session(&ephemeral.secret, &challenge.public, &salt, "bob", &bob)
+    .unwrap();
+  let error = store
+    .derive_session(
+      &challenge.id,
+      &ephemeral.public,
+      &salt,
+      "bob",
+      &client.derive_verifier(&bob),
+      &session.proof,
+    )
+    .err()
+    .unwrap();
+  assert_eq!(error.code(), SrpError::ChallengeMismatch);
+  assert!(store.is_empty());
+}
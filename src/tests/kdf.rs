@@ -0,0 +1,35 @@
+// KDF parameters come from the server, so oversized costs must fail cleanly
+use crate::{Client, KdfAlgorithm, KdfParams, SrpError};
+
+#[test]
+fn oversized_costs_are_rejected() {
+  for encoded in [
+    "$argon2id$v=19$m=4294967295,t=1,p=1",
+    "$argon2id$v=19$m=19456,t=4294967295,p=1",
+    "$argon2id$v=19$m=19456,t=2,p=4294967295",
+    "$scrypt$ln=63,r=8,p=1",
+    "$scrypt$ln=10,r=4294967295,p=1",
+    "$scrypt$ln=10,r=8,p=4294967295",
+    "$scrypt$ln=20,r=64,p=1",
+    "$pbkdf2$i=4294967295",
+  ] {
+    let error = encoded.parse::<KdfParams>().err().unwrap();
+    assert_eq!(error.code(), SrpError::InvalidKdfParams, "{}", encoded);
+  }
+
+  // Parameters built in code are checked before deriving
+  let params = KdfParams {
+    algorithm: KdfAlgorithm::Argon2id,
+    memory_cost: Some(u32::MAX),
+    iterations: None,
+    parallelism: None,
+    log_n: None,
+    block_size: None,
+  };
+  let client = Client::default();
+  let error = client
+    .derive_private_key_with(&client.generate_salt(), "alice", "password", &params)
+    .err()
+    .unwrap();
+  assert_eq!(error.code(), SrpError::InvalidKdfParams);
+}
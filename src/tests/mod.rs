@@ -0,0 +1,8 @@
+mod backends;
+mod decoy;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod handshake;
+mod kdf;
+mod rfc5054;
+mod validate;
@@ -0,0 +1,171 @@
+// Known answers from RFC 5054 Appendix B: the 1024-bit group with SHA-1,
+// k = H(N | PAD(g)) and x = H(s | H(I | ":" | P))
+#![allow(non_upper_case_globals)]
+
+use crate::params::{SrpVariant, H};
+use crate::rng::SrpRng;
+use crate::srp_integer::SrpInteger;
+use crate::{Client, Server, SrpGroup, SrpHash};
+
+const I: &str = "alice";
+const P: &str = "password123";
+const SALT: &str = "BEB25379 D1A8581E B5A72767 3A2441EE";
+const K: &str = "7556AA04 5AEF2CDD 07ABAF0F 665C3E81 8913186F";
+const X: &str = "94B7555A ABE9127C C58CCF49 93DB6CF8 4D16C124";
+const V: &str = "7E273DE8 696FFC4F 4E337D05 B4B375BE B0DDE156 9E8FA00A 9886D812 9BADA1F1
+  822223CA 1A605B53 0E379BA4 729FDC59 F105B478 7E5186F5 C671085A 1447B52A
+  48CF1970 B4FB6F84 00BBF4CE BFBB1681 52E08AB5 EA53D15C 1AFF87B2 B9DA6E04
+  E058AD51 CC72BFC9 033B564E 26480D78 E955A5E2 9E7AB245 DB2BE315 E2099AFB";
+const a: &str = "60975527 035CF2AD 1989806F 0407210B C81EDC04 E2762A56 AFD529DD DA2D4393";
+const b: &str = "E487CB59 D31AC550 471E81F0 0F6928E0 1DDA08E9 74A004F4 9E61F5D1 05284D20";
+const A: &str = "61D5E490 F6F1B795 47B0704C 436F523D D0E560F0 C64115BB 72557EC4 4352E890
+  3211C046 92272D8B 2D1A5358 A2CF1B6E 0BFCF99F 921530EC 8E393561 79EAE45E
+  42BA92AE ACED8251 71E1E8B9 AF6D9C03 E1327F44 BE087EF0 6530E69F 66615261
+  EEF54073 CA11CF58 58F0EDFD FE15EFEA B349EF5D 76988A36 72FAC47B 0769447B";
+const B: &str = "BD0C6151 2C692C0C B6D041FA 01BB152D 4916A1E7 7AF46AE1 05393011 BAF38964
+  DC46A067 0DD125B9 5A981652 236F99D9 B681CBF8 7837EC99 6C6DA044 53728610
+  D0C6DDB5 8B318885 D7D82C7F 8DEB75CE 7BD4FBAA 37089E6F 9C6059F3 88838E7A
+  00030B33 1EB76840 910440B1 B27AAEAE EB4012B7 D7665238 A8E3FB00 4B117B58";
+const U: &str = "CE38B959 3487DA98 554ED47D 70A7AE5F 462EF019";
+const S: &str = "B0DC82BA BCF30674 AE450C02 87745E79 90A3381F 63B387AA F271A10D 233861E3
+  59B48220 F7C4693C 9AE12B0A 6F67809F 0876E2D0 13800D6C 41BB59B6 D5979B5C
+  00A172B4 A2A5903A 0BDCAF8A 709585EB 2AFAFA8F 3499B200 210DCC1F 10EB3394
+  3CD67FC8 8A2F39A4 BE5BEC4E C0A3212D C346D7E4 74B29EDE 8A469FFE CA686E5A";
+
+fn bytes(hex: &str) -> Vec<u8> {
+  hex::decode(hex.split_whitespace().collect::<String>()).unwrap()
+}
+
+fn integer(hex: &str) -> SrpInteger {
+  SrpInteger::from_bytes(&bytes(hex))
+}
+
+// Hands out a fixed secret ephemeral value
+struct Fixed(Vec<u8>);
+
+impl SrpRng for Fixed {
+  fn fill(&mut self, dest: &mut [u8]) {
+    dest.copy_from_slice(&self.0);
+  }
+}
+
+fn client() -> Client {
+  Client::new(SrpGroup::RFC5054_1024, SrpHash::SHA1).with_variant(SrpVariant::Rfc5054)
+}
+
+fn server() -> Server {
+  Server::new(SrpGroup::RFC5054_1024, SrpHash::SHA1).with_variant(SrpVariant::Rfc5054)
+}
+
+#[test]
+fn multiplier() {
+  let client = client();
+  let (_, _, k) = client.group.params(SrpHash::SHA1, SrpVariant::Rfc5054);
+  assert_eq!(k.to_bytes(), bytes(K));
+}
+
+#[test]
+fn private_key() {
+  let x = client().derive_private_key(&bytes(SALT), I, P);
+  assert_eq!(*x, bytes(X));
+}
+
+#[test]
+fn verifier() {
+  assert_eq!(client().derive_verifier(&bytes(X)), bytes(V));
+}
+
+#[test]
+fn client_public_ephemeral() {
+  let ephemeral = client().with_rng(Fixed(bytes(a))).generate_ephemeral();
+  assert_eq!(*ephemeral.secret, bytes(a));
+  assert_eq!(ephemeral.public, bytes(A));
+}
+
+#[test]
+fn server_public_ephemeral() {
+  let ephemeral = server()
+    .with_rng(Fixed(bytes(b)))
+    .generate_ephemeral(&bytes(V));
+  assert_eq!(*ephemeral.secret, bytes(b));
+  assert_eq!(ephemeral.public, bytes(B));
+}
+
+#[test]
+fn scrambler() {
+  // u = H(PAD(A) | PAD(B)); A and B are already as long as N
+  let u = H(SrpHash::SHA1, &[&integer(A), &integer(B)]);
+  assert_eq!(u.to_bytes(), bytes(U));
+}
+
+#[test]
+fn premaster_secret() {
+  let client = client();
+  let (N, g, k) = client.group.params(SrpHash::SHA1, SrpVariant::Rfc5054);
+  let (a_, B_, x, u) = (integer(a), integer(B), integer(X), integer(U));
+
+  // Client: S = (B - (k * g^x)) ^ (a + (u * x)) % N
+  let S_client = B_.subtract_mult_pow(k, g, &x, &a_, &u, N);
+  assert_eq!(S_client.to_bytes(), bytes(S));
+
+  // Server: S = (A * v^u) ^ b % N
+  let S_server = integer(A)
+    .multiply(&integer(V).mod_pow_vartime(&u, N))
+    .mod_pow(&integer(b), N);
+  assert_eq!(S_server.to_bytes(), bytes(S));
+}
+
+#[test]
+fn session() {
+  // Both sides end up with K = H(S)
+  let K_ = H(SrpHash::SHA1, &[&integer(S)]).to_bytes();
+
+  let client_session = client()
+    .derive_session(&bytes(a), &bytes(B), &bytes(SALT), I, &bytes(X))
+    .unwrap();
+  assert_eq!(*client_session.key, K_);
+
+  let server_session = server()
+    .derive_session(
+      &bytes(b),
+      &bytes(A),
+      &bytes(SALT),
+      I,
+      &bytes(V),
+      &client_session.proof,
+    )
+    .unwrap();
+  assert_eq!(*server_session.key, K_);
+}
+
+// The wasm bindings take and return hex strings, and must agree too
+#[cfg(feature = "wasm")]
+#[test]
+fn wasm_handshake() {
+  let client = crate::wasm::Client {
+    inner: client().with_rng(Fixed(bytes(a))),
+  };
+  let hex = |value: &str| hex::encode(bytes(value));
+  let x = client
+    .derive_private_key(&hex(SALT), I, P.to_string())
+    .unwrap();
+  assert_eq!(x, hex(X));
+  assert_eq!(client.derive_verifier(&x).unwrap(), hex(V));
+
+  let mut handshake = client.start_handshake(I.to_string(), P.to_string());
+  assert_eq!(handshake.public(), hex(A));
+  let proof = handshake.compute_proof(&hex(SALT), &hex(B)).unwrap();
+
+  let challenge = server()
+    .with_rng(Fixed(bytes(b)))
+    .start_handshake(I, &bytes(SALT), &bytes(V));
+  assert_eq!(challenge.public(), bytes(B));
+  let session = challenge
+    .finish(&bytes(A), &hex::decode(proof).unwrap())
+    .unwrap();
+  let key = handshake.finish(&hex::encode(&session.proof)).unwrap();
+  assert_eq!(
+    key,
+    hex::encode(H(SrpHash::SHA1, &[&integer(S)]).to_bytes())
+  );
+}
@@ -0,0 +1,17 @@
+// The builtin RFC 5054 constants must pass the same checks as custom groups
+use crate::params::{Group, SrpGroup};
+use crate::validate::check_group;
+
+#[test]
+fn builtin_groups_are_valid() {
+  for group in SrpGroup::ALL {
+    let group = Group::from(group);
+    let (N, g) = group.constants();
+    let report = check_group(N, g, Some(1024));
+
+    let bits = group.prime_bytes() as u32 * 8;
+    assert!(report.valid, "{}-bit group: {:?}", bits, report.problems);
+    assert_eq!(report.bits, bits);
+    assert!(report.is_safe_prime && report.generator_in_range);
+  }
+}
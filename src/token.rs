@@ -0,0 +1,214 @@
+use crate::error::{Error, Result, SrpError};
+use crate::params::{Group, SrpHash, SrpVariant};
+use crate::rng::RngSource;
+use crate::srp_integer::{SecretInteger, SrpInteger};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+// Bumped whenever the sealed layout changes; also authenticated as AAD
+const TOKEN_VERSION: u8 = 1;
+
+const NONCE_BYTES: usize = 24;
+const KEY_BYTES: usize = 32;
+
+/// Bytes of randomness in a token id
+pub const TOKEN_ID_BYTES: usize = 16;
+
+/// Server handshake state carried between nodes in a sealed token
+pub struct HandshakeToken {
+  // Random id, so callers can record a token as spent
+  pub id: [u8; TOKEN_ID_BYTES],
+  // Started by fake_user_challenge, so it resumes without a verifier
+  pub decoy: bool,
+  // SHA-256 of the hash function, N and g the handshake was started with
+  pub group: [u8; 32],
+  // Unix time in seconds after which the token is rejected
+  pub expires: u64,
+  // SHA-256 of the verifier, which is not itself part of the token
+  pub verifier: [u8; 32],
+  pub username: String,
+  pub s: SrpInteger,
+  pub b: SecretInteger,
+  pub B: SrpInteger,
+}
+
+/// Current Unix time in seconds
+pub fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_secs())
+    .unwrap_or(0)
+}
+
+/// Identifies a group, hash function and variant, so a token only resumes
+/// on a server configured the same way
+pub fn group_fingerprint(group: &Group, hash: SrpHash, variant: SrpVariant) -> [u8; 32] {
+  let (N, g) = group.constants();
+  let N_bytes = N.to_bytes();
+  Sha256::new()
+    .chain_update([hash as u8])
+    .chain_update((N_bytes.len() as u32).to_be_bytes())
+    .chain_update(&N_bytes)
+    .chain_update(g.to_bytes())
+    .chain_update([variant as u8])
+    .finalize()
+    .into()
+}
+
+/// Binds a token to the user record, whatever the verifier's encoding
+pub fn verifier_fingerprint(v: &SrpInteger) -> [u8; 32] {
+  let bytes = v.to_bytes();
+  let start = bytes
+    .iter()
+    .position(|byte| *byte != 0)
+    .unwrap_or(bytes.len());
+  Sha256::digest(&bytes[start..]).into()
+}
+
+fn cipher(key: &[u8]) -> Result<XChaCha20Poly1305> {
+  XChaCha20Poly1305::new_from_slice(key).map_err(|_| {
+    Error::new(
+      SrpError::InvalidKey,
+      format!("Handshake token key must be {} bytes", KEY_BYTES),
+    )
+  })
+}
+
+/// Fail with InvalidKey unless `key` can seal and open tokens
+#[cfg(feature = "napi")]
+pub fn check_key(key: &[u8]) -> Result<()> {
+  cipher(key).map(drop)
+}
+
+fn invalid_token() -> Error {
+  Error::new(
+    SrpError::InvalidToken,
+    "Invalid handshake token".to_string(),
+  )
+}
+
+// Length-prefixed fields of the sealed plaintext
+fn put(out: &mut Vec<u8>, field: &[u8]) {
+  out.extend_from_slice(&(field.len() as u32).to_be_bytes());
+  out.extend_from_slice(field);
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+  if input.len() < len {
+    return Err(invalid_token());
+  }
+  let (field, rest) = input.split_at(len);
+  *input = rest;
+  Ok(field)
+}
+
+fn take_field<'a>(input: &mut &'a [u8]) -> Result<&'a [u8]> {
+  let len = take(input, 4)?;
+  take(
+    input,
+    u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize,
+  )
+}
+
+impl HandshakeToken {
+  /// Encrypt and authenticate the state under a 32-byte server key.
+  /// The token is version || nonce || ciphertext, in unpadded base64url.
+  pub fn seal(&self, key: &[u8], rng: &RngSource) -> Result<String> {
+    let cipher = cipher(key)?;
+
+    let mut plaintext = Zeroizing::new(Vec::new());
+    plaintext.extend_from_slice(&self.id);
+    plaintext.push(self.decoy as u8);
+    plaintext.extend_from_slice(&self.group);
+    plaintext.extend_from_slice(&self.expires.to_be_bytes());
+    plaintext.extend_from_slice(&self.verifier);
+    put(&mut plaintext, self.username.as_bytes());
+    put(&mut plaintext, &self.s.to_bytes());
+    put(&mut plaintext, &Zeroizing::new(self.b.to_bytes()));
+    put(&mut plaintext, &self.B.to_bytes());
+
+    let mut nonce = [0u8; NONCE_BYTES];
+    rng.fill(&mut nonce);
+    let ciphertext = cipher
+      .encrypt(
+        XNonce::from_slice(&nonce),
+        Payload {
+          msg: &plaintext,
+          aad: &[TOKEN_VERSION],
+        },
+      )
+      .expect("XChaCha20-Poly1305 seals messages of any practical size");
+
+    let mut token = Vec::with_capacity(1 + NONCE_BYTES + ciphertext.len());
+    token.push(TOKEN_VERSION);
+    token.extend_from_slice(&nonce);
+    token.extend_from_slice(&ciphertext);
+    Ok(URL_SAFE_NO_PAD.encode(token))
+  }
+
+  /// Decrypt a token produced by seal. Tampered tokens, tokens sealed under
+  /// another key and unknown versions are all rejected the same way.
+  pub fn open(token: &str, key: &[u8]) -> Result<Self> {
+    let cipher = cipher(key)?;
+
+    let token = URL_SAFE_NO_PAD
+      .decode(token.trim())
+      .map_err(|_| invalid_token())?;
+    let mut input = token.as_slice();
+    let version = take(&mut input, 1)?[0];
+    let nonce = take(&mut input, NONCE_BYTES)?;
+    if version != TOKEN_VERSION {
+      return Err(invalid_token());
+    }
+
+    let plaintext = Zeroizing::new(
+      cipher
+        .decrypt(
+          XNonce::from_slice(nonce),
+          Payload {
+            msg: input,
+            aad: &[version],
+          },
+        )
+        .map_err(|_| invalid_token())?,
+    );
+
+    let mut input = plaintext.as_slice();
+    let id = take(&mut input, TOKEN_ID_BYTES)?
+      .try_into()
+      .expect("id-sized slice");
+    let decoy = match take(&mut input, 1)?[0] {
+      0 => false,
+      1 => true,
+      _ => return Err(invalid_token()),
+    };
+    let group = take(&mut input, 32)?.try_into().expect("32-byte slice");
+    let expires = u64::from_be_bytes(take(&mut input, 8)?.try_into().expect("8-byte slice"));
+    let verifier = take(&mut input, 32)?.try_into().expect("32-byte slice");
+    let username =
+      String::from_utf8(take_field(&mut input)?.to_vec()).map_err(|_| invalid_token())?;
+    let s = SrpInteger::from_bytes(take_field(&mut input)?);
+    let b = SecretInteger::new(SrpInteger::from_bytes(take_field(&mut input)?));
+    let B = SrpInteger::from_bytes(take_field(&mut input)?);
+    if !input.is_empty() {
+      return Err(invalid_token());
+    }
+
+    Ok(HandshakeToken {
+      id,
+      decoy,
+      group,
+      expires,
+      verifier,
+      username,
+      s,
+      b,
+      B,
+    })
+  }
+}
@@ -0,0 +1,146 @@
+use crate::params::check_public_ephemeral;
+use crate::rng::RngSource;
+use crate::srp_integer::SrpInteger;
+#[cfg(feature = "napi")]
+use napi_derive::napi;
+
+// Smallest prime size accepted unless the caller lowers it
+const DEFAULT_MIN_BITS: u32 = 2048;
+
+// Miller-Rabin rounds; a composite passes all of them with probability <= 4^-40
+const MILLER_RABIN_ROUNDS: u32 = 40;
+
+const SMALL_PRIMES: [u8; 54] = [
+  2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+  101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+  197, 199, 211, 223, 227, 229, 233, 239, 241, 251,
+];
+
+/// Result of validating a group's N and g
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupReport {
+  /// Whether every check passed
+  pub valid: bool,
+  /// Bit length of N
+  pub bits: u32,
+  /// N is at least the minimum bit length
+  pub meets_minimum_bits: bool,
+  /// N is a probable prime
+  pub is_prime: bool,
+  /// N is a probable prime and so is (N - 1) / 2
+  pub is_safe_prime: bool,
+  /// g lies in [2, N - 2]. With N a safe prime this is enough for g to
+  /// generate a subgroup of order (N - 1) / 2 or N - 1
+  pub generator_in_range: bool,
+  /// Human-readable description of every failed check
+  pub problems: Vec<String>,
+}
+
+// Miller-Rabin with random bases, after trial division by small primes
+fn is_probable_prime(n: &SrpInteger, rng: &RngSource) -> bool {
+  let one = SrpInteger::from_bytes(&[1]);
+  let two = SrpInteger::from_bytes(&[2]);
+  let three = SrpInteger::from_bytes(&[3]);
+
+  for p in SMALL_PRIMES {
+    let p = SrpInteger::from_bytes(&[p]);
+    if n.equals(&p) {
+      return true;
+    }
+    if n.is_less_than(&p) || n.modulo(&p).is_zero() {
+      return false;
+    }
+  }
+
+  // n - 1 = d * 2^r with d odd
+  let n_minus_1 = n.subtract(&one);
+  let mut d = n_minus_1.shift_right(1);
+  let mut r = 1;
+  while !d.is_odd() {
+    d = d.shift_right(1);
+    r += 1;
+  }
+
+  // Bases are drawn from [2, n - 2], with extra bytes to flatten the bias
+  let n_minus_3 = n.subtract(&three);
+  let base_bytes = n.bits().div_ceil(8) as usize + 8;
+
+  'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+    let a = SrpInteger::random_integer(rng, base_bytes)
+      .modulo(&n_minus_3)
+      .add(&two);
+
+    let mut x = a.mod_pow_vartime(&d, n);
+    if x.equals(&one) || x.equals(&n_minus_1) {
+      continue;
+    }
+
+    for _ in 1..r {
+      x = x.mod_pow_vartime(&two, n);
+      if x.equals(&n_minus_1) {
+        continue 'witness;
+      }
+    }
+
+    return false;
+  }
+
+  true
+}
+
+/// Check that a big-endian prime N is a large safe prime and that g
+/// generates a large subgroup. N needs at least `min_bits` bits (defaults
+/// to 2048).
+pub fn validate_group(prime: &[u8], generator: &[u8], min_bits: Option<u32>) -> GroupReport {
+  check_group(
+    &SrpInteger::from_bytes(prime),
+    &SrpInteger::from_bytes(generator),
+    min_bits,
+  )
+}
+
+pub(crate) fn check_group(N: &SrpInteger, g: &SrpInteger, min_bits: Option<u32>) -> GroupReport {
+  let min_bits = min_bits.unwrap_or(DEFAULT_MIN_BITS);
+  let rng = RngSource::os();
+  let mut problems = Vec::new();
+
+  let bits = N.bits();
+  let meets_minimum_bits = bits >= min_bits;
+  if !meets_minimum_bits {
+    problems.push(format!(
+      "N has {} bits, fewer than the required {}",
+      bits, min_bits
+    ));
+  }
+
+  // q = (N - 1) / 2 is only worth testing once N itself is prime
+  let q = N.shift_right(1);
+  let is_prime = is_probable_prime(N, &rng);
+  let is_safe_prime = is_prime && is_probable_prime(&q, &rng);
+  if !is_prime {
+    problems.push("N is not prime".to_string());
+  } else if !is_safe_prime {
+    problems.push("N is not a safe prime, (N - 1) / 2 is not prime".to_string());
+  }
+
+  let generator_error = check_public_ephemeral(g, N);
+  let generator_in_range = generator_error.is_none();
+  if let Some(reason) = generator_error {
+    problems.push(format!("g {}", reason));
+  }
+
+  // No separate order check for g: the order divides N - 1 = 2q, and only
+  // 1 and N - 1 have order 1 or 2, so with N a safe prime every g in
+  // [2, N - 2] has order q or 2q
+
+  GroupReport {
+    valid: problems.is_empty(),
+    bits,
+    meets_minimum_bits,
+    is_prime,
+    is_safe_prime,
+    generator_in_range,
+    problems,
+  }
+}
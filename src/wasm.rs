@@ -0,0 +1,152 @@
+// wasm-bindgen bindings of the client side, for browsers. Values are hex
+// strings, decoded and encoded exactly like the Node-API bindings do by
+// default, so both produce the same salts, keys, verifiers and proofs.
+use crate::client::{Client as SrpClient, ClientHandshake as Handshake, Credentials};
+use crate::error::{Error, SrpError};
+use crate::params::{SrpGroup, SrpHash, SECRET_BYTES};
+use crate::rng::RngSource;
+use crate::srp_integer::{SecretInteger, SrpInteger};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+/// Result of every binding, thrown as a JS Error with its SrpError as `code`
+type Result<T> = std::result::Result<T, JsValue>;
+
+fn integer(hex: &str) -> Result<SrpInteger> {
+  Ok(SrpInteger::from_hex(hex).map_err(|e| Error::new(SrpError::InvalidHex, e))?)
+}
+
+fn secret(hex: &str) -> Result<SecretInteger> {
+  Ok(SecretInteger::from_hex(hex).map_err(|e| Error::new(SrpError::InvalidHex, e))?)
+}
+
+/// Generate a random salt for password hashing
+#[wasm_bindgen(js_name = generateSalt)]
+pub fn generate_salt() -> String {
+  // s    User's salt
+  let s = SrpInteger::random_integer(&RngSource::os(), SECRET_BYTES);
+  s.to_hex()
+}
+
+/// Derive the private key from user credentials
+#[wasm_bindgen(js_name = derivePrivateKey)]
+pub fn derive_private_key(salt: &str, username: &str, password: String) -> Result<String> {
+  Client::new(None, None).derive_private_key(salt, username, password)
+}
+
+/// Derive the password verifier from the private key
+#[wasm_bindgen(js_name = deriveVerifier)]
+pub fn derive_verifier(private_key: &str) -> Result<String> {
+  Client::new(None, None).derive_verifier(private_key)
+}
+
+/// Client-side SRP implementation
+#[wasm_bindgen]
+pub struct Client {
+  pub(crate) inner: SrpClient,
+}
+
+#[wasm_bindgen]
+impl Client {
+  /// Create a new Client instance with optional builtin group and hash function
+  #[wasm_bindgen(constructor)]
+  pub fn new(group: Option<SrpGroup>, hash: Option<SrpHash>) -> Client {
+    Client {
+      inner: SrpClient::new(group.unwrap_or_default(), hash.unwrap_or_default()),
+    }
+  }
+
+  /// Generate a random salt for password hashing
+  #[wasm_bindgen(js_name = generateSalt)]
+  pub fn generate_salt(&self) -> String {
+    self.inner.salt().to_hex()
+  }
+
+  /// Derive the private key from user credentials
+  #[wasm_bindgen(js_name = derivePrivateKey)]
+  pub fn derive_private_key(&self, salt: &str, username: &str, password: String) -> Result<String> {
+    // s    User's salt
+    let s = integer(salt)?;
+    let password = Zeroizing::new(password);
+
+    let x = self.inner.private_key(&s, username, &password, None)?;
+
+    Ok(x.to_hex())
+  }
+
+  /// Derive the password verifier from the private key
+  #[wasm_bindgen(js_name = deriveVerifier)]
+  pub fn derive_verifier(&self, private_key: &str) -> Result<String> {
+    // x    Private key (derived from password and salt)
+    let x = secret(private_key)?;
+
+    Ok(self.inner.verifier(&x).to_hex())
+  }
+
+  /// Start a handshake from the user's password. It keeps the secret
+  /// ephemeral and the session to itself.
+  #[wasm_bindgen(js_name = startHandshake)]
+  pub fn start_handshake(&self, username: String, password: String) -> ClientHandshake {
+    self.handshake(
+      username,
+      Credentials::Password(Zeroizing::new(password), None),
+    )
+  }
+
+  /// Start a handshake from a private key derived at registration
+  #[wasm_bindgen(js_name = startHandshakeWithPrivateKey)]
+  pub fn start_handshake_with_private_key(
+    &self,
+    username: String,
+    private_key: &str,
+  ) -> Result<ClientHandshake> {
+    let x = secret(private_key)?;
+    Ok(self.handshake(username, Credentials::PrivateKey(x)))
+  }
+}
+
+impl Client {
+  fn handshake(&self, username: String, credentials: Credentials) -> ClientHandshake {
+    ClientHandshake {
+      inner: self.inner.handshake(username, credentials),
+    }
+  }
+}
+
+/// Client side of a single handshake, created by Client.startHandshake
+#[wasm_bindgen]
+pub struct ClientHandshake {
+  inner: Handshake,
+}
+
+#[wasm_bindgen]
+impl ClientHandshake {
+  /// Client's public ephemeral value A, to send to the server
+  #[wasm_bindgen(getter)]
+  pub fn public(&self) -> String {
+    self.inner.A.to_hex()
+  }
+
+  /// Derive the session from the user's salt and the server's public
+  /// ephemeral value B, and return the client's proof M1 for the server
+  #[wasm_bindgen(js_name = computeProof)]
+  pub fn compute_proof(&mut self, salt: &str, server_public_ephemeral: &str) -> Result<String> {
+    // s    User's salt
+    let s = integer(salt)?;
+
+    // B    Server's public ephemeral value
+    let B = integer(server_public_ephemeral)?;
+
+    Ok(self.inner.prove(&s, &B)?.to_hex())
+  }
+
+  /// Verify the server's proof M2 and return the session key.
+  /// The session is dropped whatever the outcome.
+  pub fn finish(&mut self, server_session_proof: &str) -> Result<String> {
+    // M2    Server's proof
+    let M2_bytes = hex::decode(server_session_proof.trim())
+      .map_err(|e| Error::new(SrpError::InvalidHex, e.to_string()))?;
+
+    Ok(self.inner.verify(&M2_bytes)?.to_hex())
+  }
+}